@@ -179,100 +179,248 @@
 // What is the total number of distinct ways you can arrange the adapters to connect the charging
 // outlet to your device?
 
+use num_bigint::BigUint;
+use std::collections::BTreeMap;
+use std::fmt;
+
 fn main() {
     let input: Vec<_> = include_str!("../input")
         .lines()
         .map(|l| -> usize { l.parse().unwrap() })
         .collect();
 
-    let part_1 = part_1(&input);
+    // cargo run --bin day_10 -- <max step> [arrangements to list]
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        if let Err(error) = analyse(&input, &args) {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let part_1 = part_1(&input).unwrap_or_else(|error| panic!("{}", error));
     assert_eq!(part_1, 1914);
     println!("Part 1: {}", part_1);
 
-    let part_2 = part_2(&input);
-    assert_eq!(part_2, 9256148959232);
+    let part_2 = part_2(&input).unwrap_or_else(|error| panic!("{}", error));
+    assert_eq!(part_2, BigUint::from(9256148959232u64));
     println!("Part 2: {}", part_2);
 }
 
-fn prepare(input: &[usize]) -> (usize, usize, Vec<usize>) {
-    let mut input = input.to_vec();
-    input.sort_unstable();
+fn analyse(input: &[usize], args: &[String]) -> Result<(), String> {
+    let max_step = args[0]
+        .parse()
+        .map_err(|_| format!("invalid max step: {}", args[0]))?;
+    let list = match args.get(1) {
+        Some(n) => n.parse().map_err(|_| format!("invalid count: {}", n))?,
+        None => 0,
+    };
+
+    let adapters = Adapters::new(input, max_step).map_err(|error| error.to_string())?;
+
+    println!("Device: {} jolts", adapters.device_joltage());
+    for (step, count) in adapters.differences() {
+        println!("Differences of {}: {}", step, count);
+    }
+    println!("Arrangements: {}", adapters.count_arrangements());
+    println!("Fewest adapters: {}", adapters.fewest().len());
+
+    for arrangement in adapters.arrangements().take(list) {
+        let joltages: Vec<_> = arrangement.iter().map(|j| j.to_string()).collect();
+        println!(
+            "(0), {}, ({})",
+            joltages.join(", "),
+            adapters.device_joltage()
+        );
+    }
+
+    Ok(())
+}
 
-    let device_joltage = input.last().unwrap() + 3;
-    input.push(device_joltage);
+#[derive(Debug, PartialEq)]
+enum Error {
+    Empty,
+    // Adapters can't step up by 0 jolts.
+    ZeroStep,
+    // More than one adapter has this rating.
+    Duplicate(usize),
+    // No adapter can take the output of the one before it.
+    Gap { from: usize, to: usize },
+}
 
-    let starting_joltage = 0;
-    input.insert(0, starting_joltage);
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Empty => write!(f, "no adapters"),
+            Error::ZeroStep => write!(f, "the max step must be at least 1 jolt"),
+            Error::Duplicate(joltage) => write!(f, "more than one adapter rated {}", joltage),
+            Error::Gap { from, to } => write!(f, "no adapter fits between {} and {}", from, to),
+        }
+    }
+}
 
-    (starting_joltage, device_joltage, input)
+// The charging outlet, every adapter in joltage order, and the device. Each adapter can take an
+// input up to `max_step` jolts lower than its rating.
+struct Adapters {
+    joltages: Vec<usize>,
+    max_step: usize,
 }
 
-fn part_1(input: &[usize]) -> usize {
-    let (starting_joltage, _device_joltage, input) = prepare(&input);
+impl Adapters {
+    fn new(input: &[usize], max_step: usize) -> Result<Self, Error> {
+        if max_step == 0 {
+            return Err(Error::ZeroStep);
+        }
+
+        let mut joltages = input.to_vec();
+        joltages.sort_unstable();
+
+        // Your device has a built-in joltage adapter rated for `max_step` jolts higher than the
+        // highest-rated adapter in your bag.
+        let device_joltage = joltages.last().ok_or(Error::Empty)? + max_step;
+        joltages.push(device_joltage);
 
-    let mut one = 0;
-    let mut thr = 0;
+        // The charging outlet has an effective joltage rating of 0.
+        joltages.insert(0, 0);
 
-    let mut last = starting_joltage;
-    for n in input.into_iter().skip(1) {
-        match n - last {
-            1 => one += 1,
-            2 => {}
-            3 => thr += 1,
-            _ => panic!("no adaptor fits"),
+        // Any arrangement has to bridge every gap in the full chain as well, so if one is too wide
+        // nothing can connect the outlet to the device.
+        for pair in joltages.windows(2) {
+            if pair[1] == pair[0] {
+                return Err(Error::Duplicate(pair[0]));
+            }
+            if pair[1] - pair[0] > max_step {
+                return Err(Error::Gap {
+                    from: pair[0],
+                    to: pair[1],
+                });
+            }
         }
-        last = n;
+
+        Ok(Self { joltages, max_step })
+    }
+
+    fn device_joltage(&self) -> usize {
+        *self.joltages.last().unwrap()
     }
 
-    one * thr
+    // How many times each joltage difference appears in the chain that uses every adapter.
+    fn differences(&self) -> BTreeMap<usize, usize> {
+        let mut differences = BTreeMap::new();
+
+        for pair in self.joltages.windows(2) {
+            *differences.entry(pair[1] - pair[0]).or_insert(0) += 1;
+        }
+
+        differences
+    }
+
+    // Indexes of the adapters (or device) that can take the output of adapter `i`.
+    fn next(&self, i: usize) -> impl Iterator<Item = usize> + '_ {
+        let joltage = self.joltages[i];
+
+        (i + 1..self.joltages.len())
+            .take_while(move |j| self.joltages[*j] - joltage <= self.max_step)
+    }
+
+    // (0),1,4,5,6,7,10,11,12,15,16,19,(22)
+    // 22 (end)
+    // 19 -> 1 (only 1 path from 19 to end)
+    // 16 -> 1 (16 can only reach 19, so still only 1 path)
+    // 15 -> 1 (same)
+    // 12 -> 1 (same)
+    // 11 -> 1 (same)
+    // 10 -> 1 + 1 = 2 (10 can reach 11 AND 12, one path each = 2)
+    // 7 -> 2 (7 can only reach 10, so still 2)
+    // 6 -> 2 (same)
+    // 5 -> 2 + 2 = 4 (5 can go via 6 (2 paths) and 7 (2 paths) = 4)
+    // 4 -> 4 + 2 + 2 = 8 (4 can go via 5 (4 paths), 6 (2 paths) and 7 (2 paths) = 8)
+    // 1 -> 8 (1 can only reach 4, so still 8 paths)
+    // 0 -> 8 (0 can only reach 1, so the answer is 8)
+    fn count_arrangements(&self) -> BigUint {
+        let len = self.joltages.len();
+        let mut paths = vec![BigUint::from(0u8); len];
+        paths[len - 1] = BigUint::from(1u8);
+
+        for i in (0..len - 1).rev() {
+            paths[i] = self.next(i).map(|j| &paths[j]).sum();
+        }
+
+        paths.swap_remove(0)
+    }
+
+    // Every arrangement of adapters, generated one at a time.
+    fn arrangements(&self) -> Arrangements<'_> {
+        Arrangements {
+            adapters: self,
+            stack: vec![(0, 1)],
+        }
+    }
+
+    // The fewest adapters that still connect the outlet to the device. Always jumping to the
+    // highest adapter in reach can't do worse than any other choice.
+    fn fewest(&self) -> Vec<usize> {
+        let device = self.joltages.len() - 1;
+        let mut chain = Vec::new();
+        let mut i = 0;
+
+        loop {
+            i = self.next(i).last().unwrap();
+            if i == device {
+                return chain;
+            }
+            chain.push(self.joltages[i]);
+        }
+    }
+}
+
+// A depth first walk over the arrangements. Each stack entry is an adapter in the current chain
+// and the next adapter to try after it.
+struct Arrangements<'a> {
+    adapters: &'a Adapters,
+    stack: Vec<(usize, usize)>,
+}
+
+impl Iterator for Arrangements<'_> {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let joltages = &self.adapters.joltages;
+        let device = joltages.len() - 1;
+
+        while let Some((i, next)) = self.stack.pop() {
+            if next == device + 1 || joltages[next] - joltages[i] > self.adapters.max_step {
+                continue;
+            }
+            self.stack.push((i, next + 1));
+
+            if next == device {
+                let chain = self.stack[1..].iter().map(|(i, _)| joltages[*i]).collect();
+                return Some(chain);
+            }
+
+            self.stack.push((next, next + 1));
+        }
+
+        None
+    }
+}
+
+fn part_1(input: &[usize]) -> Result<usize, Error> {
+    let adapters = Adapters::new(input, 3)?;
+    let differences = adapters.differences();
+
+    let one = differences.get(&1).unwrap_or(&0);
+    let thr = differences.get(&3).unwrap_or(&0);
+
+    Ok(one * thr)
 }
 
-// (0),1,4,5,6,7,10,11,12,15,16,19,(22)
-// 22 (end)
-// 19 -> 1 (only 1 path from 19 to end)
-// 16 -> 1 (16 can only reach 19, so still only 1 path)
-// 15 -> 1 (same)
-// 12 -> 1 (same)
-// 11 -> 1 (same)
-// 10 -> 1 + 1 = 2 (10 can reach 11 AND 12, one path each = 2)
-// 7 -> 2 (7 can only reach 10, so still 2)
-// 6 -> 2 (same)
-// 5 -> 2 + 2 = 4 (5 can go via 6 (2 paths) and 7 (2 paths) = 4)
-// 4 -> 4 + 2 + 2 = 8 (4 can go via 5 (4 paths), 6 (2 paths) and 7 (2 paths) = 8)
-// 1 -> 8 (1 can only reach 4, so still 8 paths)
-// 0 -> 8 (0 can only reach 1, so the answer is 8)
-fn part_2(input: &[usize]) -> usize {
-    use std::collections::HashMap;
-
-    let (_starting_joltage, _device_joltage, input) = prepare(&input);
-    let adapters: Vec<_> = input.into_iter().rev().collect();
-
-    let mut paths_from_adapter = HashMap::new();
-
-    (1..adapters.len()).fold(0, |_acc, i| {
-        let current_adapter = adapters[i];
-
-        let count_paths = |offset| {
-            adapters
-                .get(i.wrapping_sub(offset))
-                .map(|adapter| {
-                    if adapter - current_adapter <= 3 {
-                        return paths_from_adapter.get(adapter).unwrap_or(&1);
-                    } else {
-                        &0
-                    }
-                })
-                .unwrap_or(&0)
-        };
-
-        // Count the paths from the previous 3 adapters
-        let paths = count_paths(1) + count_paths(2) + count_paths(3);
-
-        // Store how many paths for the current adapter
-        paths_from_adapter.insert(current_adapter, paths);
-
-        paths
-    })
+fn part_2(input: &[usize]) -> Result<BigUint, Error> {
+    let adapters = Adapters::new(input, 3)?;
+
+    Ok(adapters.count_arrangements())
 }
 
 #[cfg(test)]
@@ -283,15 +431,61 @@ mod tests {
     fn readme_example() {
         let input = vec![16, 10, 15, 5, 1, 11, 7, 19, 6, 12, 4];
 
-        assert_eq!(part_1(&input), 35);
-        assert_eq!(part_2(&input), 8);
+        assert_eq!(part_1(&input), Ok(35));
+        assert_eq!(part_2(&input), Ok(BigUint::from(8u8)));
 
         let input = vec![
             28, 33, 18, 42, 31, 14, 46, 20, 48, 47, 24, 23, 49, 45, 19, 38, 39, 11, 1, 32, 25, 35,
             8, 17, 7, 9, 4, 2, 34, 10, 3,
         ];
 
-        assert_eq!(part_1(&input), 220);
-        assert_eq!(part_2(&input), 19208);
+        assert_eq!(part_1(&input), Ok(220));
+        assert_eq!(part_2(&input), Ok(BigUint::from(19208u32)));
+    }
+
+    #[test]
+    fn analysis() {
+        let adapters = Adapters::new(&[16, 10, 15, 5, 1, 11, 7, 19, 6, 12, 4], 3).unwrap();
+
+        assert_eq!(
+            adapters.differences().into_iter().collect::<Vec<_>>(),
+            vec![(1, 7), (3, 5)]
+        );
+
+        let arrangements: Vec<_> = adapters.arrangements().collect();
+        assert_eq!(arrangements.len(), 8);
+        assert_eq!(arrangements[0], vec![1, 4, 5, 6, 7, 10, 11, 12, 15, 16, 19]);
+        assert_eq!(arrangements[7], vec![1, 4, 7, 10, 12, 15, 16, 19]);
+        assert_eq!(adapters.fewest(), vec![1, 4, 7, 10, 12, 15, 16, 19]);
+
+        let adapters = Adapters::new(&[1, 2, 3, 4], 4).unwrap();
+        assert_eq!(adapters.device_joltage(), 8);
+        assert_eq!(adapters.fewest(), vec![4]);
+        assert_eq!(adapters.count_arrangements(), BigUint::from(8u8));
+    }
+
+    #[test]
+    fn gaps() {
+        assert_eq!(
+            Adapters::new(&[1, 2, 6], 3).err(),
+            Some(Error::Gap { from: 2, to: 6 })
+        );
+        assert_eq!(Adapters::new(&[], 3).err(), Some(Error::Empty));
+        assert_eq!(
+            Adapters::new(&[1, 4, 4, 6], 3).err(),
+            Some(Error::Duplicate(4))
+        );
+        assert_eq!(Adapters::new(&[1, 2, 3], 0).err(), Some(Error::ZeroStep));
+        assert_eq!(part_1(&[1, 2, 6]), Err(Error::Gap { from: 2, to: 6 }));
+        assert_eq!(part_2(&[]), Err(Error::Empty));
+    }
+
+    #[test]
+    fn big_counts() {
+        // 1..=200 with steps of up to 3 is a tribonacci number, far larger than a u64.
+        let input: Vec<_> = (1..=200).collect();
+        let count = Adapters::new(&input, 3).unwrap().count_arrangements();
+
+        assert!(count.bits() > 64);
     }
 }
@@ -231,63 +231,168 @@
 // Given the new visibility method and the rule change for occupied seats becoming empty, once
 // equilibrium is reached, how many seats end up occupied?
 
+mod render;
+
+use std::collections::HashMap;
+use std::fmt;
+
 fn main() {
     let input = include_str!("../input");
 
-    let part_1 = part_1(&input);
+    // cargo run --bin day_11 -- <part> [--text] [--stats] [--gif <file>]
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        if let Err(error) = watch(input, &args) {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let part_1 = part_1(input);
     assert_eq!(part_1, 2113);
     println!("Part 1: {}", part_1);
 
-    let part_2 = part_2(&input);
+    let part_2 = part_2(input);
     assert_eq!(part_2, 1865);
     println!("Part 2: {}", part_2);
 }
 
-fn part_1(input: &str) -> usize {
-    inner(&input, 4, adjacent_seats)
-}
+const USAGE: &str = "usage: day_11 <1|2> [--text] [--stats] [--gif <file>]";
 
-fn part_2(input: &str) -> usize {
-    inner(&input, 5, visible_seats)
-}
+fn watch(input: &str, args: &[String]) -> Result<(), String> {
+    let (min_seats, rule) = match args[0].as_str() {
+        "1" => (4, Rule::Adjacent),
+        "2" => (5, Rule::Visible),
+        _ => return Err(USAGE.to_owned()),
+    };
 
-type World = Vec<Vec<char>>;
+    let mut text = false;
+    let mut stats = false;
+    let mut gif_path = None;
 
-fn inner<F>(input: &str, min_seats: usize, count_seats: F) -> usize
-where
-    F: Fn(&World, usize, usize) -> usize,
-{
-    let input: World = input.lines().map(|l| l.chars().collect()).collect();
-    let width = input.first().unwrap().len();
-    let height = input.len();
-
-    let mut old = input.clone();
-    let mut new = input;
-
-    loop {
-        for x in 0..width {
-            for y in 0..height {
-                // If a seat is empty (L) and there are no occupied seats adjacent to it, the seat
-                // becomes occupied.
-                if old[y][x] == 'L' && count_seats(&old, x, y) == 0 {
-                    new[y][x] = '#';
-                // If a seat is occupied (#) and five or more seats adjacent to it are also
-                // occupied, the seat becomes empty.
-                } else if old[y][x] == '#' && count_seats(&old, x, y) >= min_seats {
-                    new[y][x] = 'L';
+    let mut iter = args[1..].iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--text" => text = true,
+            "--stats" => stats = true,
+            "--gif" => gif_path = Some(iter.next().ok_or(USAGE)?),
+            _ => return Err(USAGE.to_owned()),
+        }
+    }
+
+    let mut gif = None;
+    let mut error = None;
+
+    let outcome = inner(input, min_seats, rule, |frame| {
+        if text {
+            println!("{}", render::text(frame));
+        }
+        if stats {
+            println!("{}", render::stats(frame));
+        }
+        if let Some(path) = gif_path {
+            // The first frame gives the image size.
+            if gif.is_none() && error.is_none() {
+                match render::Gif::create(path, frame, 4) {
+                    Ok(created) => gif = Some(created),
+                    Err(e) => error = Some(e),
                 }
-                // Otherwise, the seat's state does not change.
+            }
+            if let Some(Err(e)) = gif.as_mut().map(|gif| gif.write(frame)) {
+                error = Some(e);
+                gif = None;
             }
         }
+    })
+    .map_err(|error| error.to_string())?;
 
-        if old == new {
-            break;
-        }
+    if let Some(error) = error {
+        return Err(format!("{}: {}", gif_path.unwrap(), error));
+    }
+
+    println!("{:?}", outcome);
+    Ok(())
+}
+
+fn part_1(input: &str) -> usize {
+    let outcome = inner(input, 4, Rule::Adjacent, |_| {});
+    stable_occupied(outcome.unwrap_or_else(|error| panic!("{}", error)))
+}
 
-        old = new.clone();
+fn part_2(input: &str) -> usize {
+    let outcome = inner(input, 5, Rule::Visible, |_| {});
+    stable_occupied(outcome.unwrap_or_else(|error| panic!("{}", error)))
+}
+
+fn stable_occupied(outcome: Outcome) -> usize {
+    match outcome {
+        Outcome::Stable { occupied, .. } => occupied,
+        Outcome::Cycle { start, period } => panic!(
+            "seats never settle, from generation {} they repeat every {} generations",
+            start, period
+        ),
     }
+}
 
-    new.iter().flatten().filter(|c| c == &&'#').count()
+#[derive(Debug, Clone, Copy)]
+enum Rule {
+    // One of the eight positions immediately up, down, left, right, or diagonal from the seat
+    Adjacent,
+    // The first seat in each of those eight directions
+    Visible,
+}
+
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    // No seats changed after `generations` rounds.
+    Stable { generations: usize, occupied: usize },
+    // The layout at generation `start + period` is the same as at `start`, so it will never
+    // settle.
+    Cycle { start: usize, period: usize },
+}
+
+#[derive(Debug, PartialEq)]
+enum Error {
+    // There are no rows, or the first row is blank.
+    Empty,
+    // Every row has to be as wide as the first.
+    Width {
+        line: usize,
+        width: usize,
+        expected: usize,
+    },
+    Cell {
+        line: usize,
+        column: usize,
+        character: char,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Empty => write!(f, "the layout is empty"),
+            Error::Width {
+                line,
+                width,
+                expected,
+            } => write!(
+                f,
+                "line {}: the row is {} wide, expected {}",
+                line, width, expected
+            ),
+            Error::Cell {
+                line,
+                column,
+                character,
+            } => write!(
+                f,
+                "line {}, column {}: {:?} is not floor or a seat",
+                line, column, character
+            ),
+        }
+    }
 }
 
 #[rustfmt::skip]
@@ -297,65 +402,260 @@ const POSITIONS: [(isize, isize); 8] = [
     ( 1, -1), ( 1, 0), ( 1, 1),
 ];
 
-// One of the eight positions immediately up, down, left, right, or diagonal from the seat
-fn adjacent_seats(world: &World, x: usize, y: usize) -> usize {
-    POSITIONS
-        .iter()
-        .filter(|(yy, xx)| {
-            world
-                .get((y as isize + *yy) as usize)
-                .map(|row| row.get((x as isize + *xx) as usize) == Some(&'#'))
-                .unwrap_or(false)
+// The seats in the waiting area. Floor never changes, so only seats are simulated.
+struct Layout {
+    width: usize,
+    height: usize,
+    // The seat index at each position, row by row, or None for floor.
+    grid: Vec<Option<usize>>,
+    // Whether each seat starts occupied.
+    initial: Vec<bool>,
+}
+
+impl Layout {
+    fn parse(input: &str) -> Result<Self, Error> {
+        let rows: Vec<_> = input.lines().collect();
+        let height = rows.len();
+        let width = rows.first().map(|row| row.chars().count()).unwrap_or(0);
+        if width == 0 {
+            return Err(Error::Empty);
+        }
+
+        let mut grid = Vec::with_capacity(width * height);
+        let mut initial = Vec::new();
+
+        for (line, row) in rows.iter().enumerate() {
+            let line = line + 1;
+
+            for (column, c) in row.chars().enumerate() {
+                match c {
+                    '.' => grid.push(None),
+                    'L' | '#' => {
+                        grid.push(Some(initial.len()));
+                        initial.push(c == '#');
+                    }
+                    _ => {
+                        return Err(Error::Cell {
+                            line,
+                            column: column + 1,
+                            character: c,
+                        })
+                    }
+                }
+            }
+
+            if grid.len() != line * width {
+                return Err(Error::Width {
+                    line,
+                    width: row.chars().count(),
+                    expected: width,
+                });
+            }
+        }
+
+        Ok(Self {
+            width,
+            height,
+            grid,
+            initial,
         })
-        .count()
+    }
+
+    fn seat_at(&self, x: isize, y: isize) -> Option<Option<usize>> {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return None;
+        }
+
+        Some(self.grid[y as usize * self.width + x as usize])
+    }
+
+    // For each seat, the seats it takes into account under the rule. These never change, so
+    // they're traced once up front rather than every generation.
+    fn neighbours(&self, rule: Rule) -> Vec<Vec<usize>> {
+        let mut neighbours = vec![Vec::new(); self.initial.len()];
+
+        for y in 0..self.height as isize {
+            for x in 0..self.width as isize {
+                let seat = match self.seat_at(x, y) {
+                    Some(Some(seat)) => seat,
+                    _ => continue,
+                };
+
+                for (dy, dx) in POSITIONS.iter() {
+                    let mut i = 1;
+                    while let Some(cell) = self.seat_at(x + dx * i, y + dy * i) {
+                        if let Some(other) = cell {
+                            neighbours[seat].push(other);
+                            break;
+                        }
+                        if let Rule::Adjacent = rule {
+                            break;
+                        }
+                        i += 1;
+                    }
+                }
+            }
+        }
+
+        neighbours
+    }
+}
+
+struct Simulation {
+    neighbours: Vec<Vec<usize>>,
+    min_seats: usize,
+    occupied: Vec<bool>,
+    // How many of each seat's neighbours are occupied.
+    counts: Vec<usize>,
+    // Seats that might change next generation: those that changed last time and their
+    // neighbours. Nothing else can have seen a difference.
+    dirty: Vec<usize>,
 }
 
-fn visible_seats(world: &World, x: usize, y: usize) -> usize {
-    fn check<F, G>(world: &World, f: F, g: G) -> usize
-    where
-        F: Fn(usize) -> usize,
-        G: Fn(usize) -> usize,
-    {
-        let mut i = 1;
-        while let Some(seat) = world.get(f(i)).and_then(|row| row.get(g(i))) {
-            match seat {
-                '#' => return 1,
-                'L' => return 0,
-                '.' => {}
-                _ => panic!("bad input"),
+impl Simulation {
+    fn new(layout: &Layout, min_seats: usize, rule: Rule) -> Self {
+        let neighbours = layout.neighbours(rule);
+        let occupied = layout.initial.clone();
+        let counts = neighbours
+            .iter()
+            .map(|seats| seats.iter().filter(|seat| occupied[**seat]).count())
+            .collect();
+
+        Self {
+            dirty: (0..occupied.len()).collect(),
+            neighbours,
+            min_seats,
+            occupied,
+            counts,
+        }
+    }
+
+    // Applies the rules to every seat at once, returning the seats that changed.
+    fn step(&mut self) -> Vec<usize> {
+        let changed: Vec<_> = self
+            .dirty
+            .iter()
+            .copied()
+            .filter(|seat| {
+                let count = self.counts[*seat];
+                if self.occupied[*seat] {
+                    // If a seat is occupied (#) and enough seats it can see are also occupied,
+                    // the seat becomes empty.
+                    count >= self.min_seats
+                } else {
+                    // If a seat is empty (L) and there are no occupied seats it can see, the seat
+                    // becomes occupied.
+                    count == 0
+                }
+                // Otherwise, the seat's state does not change.
+            })
+            .collect();
+
+        let mut dirty = vec![false; self.occupied.len()];
+
+        for seat in &changed {
+            let now_occupied = !self.occupied[*seat];
+            self.occupied[*seat] = now_occupied;
+            dirty[*seat] = true;
+
+            for other in &self.neighbours[*seat] {
+                if now_occupied {
+                    self.counts[*other] += 1;
+                } else {
+                    self.counts[*other] -= 1;
+                }
+                dirty[*other] = true;
             }
-            i += 1;
         }
-        0
+
+        self.dirty = (0..dirty.len()).filter(|seat| dirty[*seat]).collect();
+
+        changed
     }
 
-    let mut count = 0;
+    fn occupied_count(&self) -> usize {
+        self.occupied.iter().filter(|o| **o).count()
+    }
 
-    // Check left
-    count += check(&world, |_| y, |i| x.wrapping_sub(i));
+    // The occupied seats packed into bits, to remember which layouts have been seen.
+    fn fingerprint(&self) -> Vec<u64> {
+        self.occupied
+            .chunks(64)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .enumerate()
+                    .fold(0, |acc, (i, o)| acc | (*o as u64) << i)
+            })
+            .collect()
+    }
+}
 
-    // Check right
-    count += check(&world, |_| y, |i| x + i);
+// A generation of the simulation, passed to `inner`'s observer.
+struct Frame<'a> {
+    generation: usize,
+    layout: &'a Layout,
+    occupied: &'a [bool],
+    // How many seats changed to get to this generation.
+    changed: usize,
+}
 
-    // Check up
-    count += check(&world, |i| y.wrapping_sub(i), |_| x);
+impl Frame<'_> {
+    fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = render::Cell> + '_> + '_ {
+        self.layout.grid.chunks(self.layout.width).map(move |row| {
+            row.iter().map(move |cell| match cell {
+                None => render::Cell::Floor,
+                Some(seat) if self.occupied[*seat] => render::Cell::Occupied,
+                Some(_) => render::Cell::Empty,
+            })
+        })
+    }
+}
 
-    // Check down
-    count += check(&world, |i| y + i, |_| x);
+// Runs the simulation until it settles or repeats, calling `observe` with the starting layout and
+// then every generation that differs from the one before.
+fn inner<F>(input: &str, min_seats: usize, rule: Rule, mut observe: F) -> Result<Outcome, Error>
+where
+    F: FnMut(&Frame),
+{
+    let layout = Layout::parse(input)?;
+    let mut simulation = Simulation::new(&layout, min_seats, rule);
+
+    let mut seen = HashMap::new();
+    seen.insert(simulation.fingerprint(), 0);
 
-    // Check left/up
-    count += check(&world, |i| y.wrapping_sub(i), |i| x.wrapping_sub(i));
+    observe(&Frame {
+        generation: 0,
+        layout: &layout,
+        occupied: &simulation.occupied,
+        changed: 0,
+    });
 
-    // Check left/down
-    count += check(&world, |i| y + i, |i| x.wrapping_sub(i));
+    for generation in 1.. {
+        let changed = simulation.step();
+        if changed.is_empty() {
+            return Ok(Outcome::Stable {
+                generations: generation - 1,
+                occupied: simulation.occupied_count(),
+            });
+        }
 
-    // Check right/up
-    count += check(&world, |i| y.wrapping_sub(i), |i| x + i);
+        observe(&Frame {
+            generation,
+            layout: &layout,
+            occupied: &simulation.occupied,
+            changed: changed.len(),
+        });
 
-    // Check right/down
-    count += check(&world, |i| y + i, |i| x + i);
+        if let Some(start) = seen.insert(simulation.fingerprint(), generation) {
+            return Ok(Outcome::Cycle {
+                start,
+                period: generation - start,
+            });
+        }
+    }
 
-    count
+    unreachable!()
 }
 
 #[cfg(test)]
@@ -375,7 +675,89 @@ LLLLLLLLLL
 L.LLLLLL.L
 L.LLLLL.LL";
 
-        assert_eq!(part_1(&input), 37);
-        assert_eq!(part_2(&input), 26);
+        assert_eq!(part_1(input), 37);
+        assert_eq!(part_2(input), 26);
+
+        assert_eq!(
+            inner(input, 4, Rule::Adjacent, |_| {}),
+            Ok(Outcome::Stable {
+                generations: 5,
+                occupied: 37
+            })
+        );
+        assert_eq!(
+            inner(input, 5, Rule::Visible, |_| {}),
+            Ok(Outcome::Stable {
+                generations: 6,
+                occupied: 26
+            })
+        );
+    }
+
+    #[test]
+    fn visible_neighbours() {
+        // The empty seat below would see eight occupied seats
+        let input = ".......#.
+...#.....
+.#.......
+.........
+..#L....#
+....#....
+.........
+#........
+...#.....";
+
+        let layout = Layout::parse(input).unwrap();
+        let seat = layout.grid[4 * 9 + 3].unwrap();
+        let neighbours = layout.neighbours(Rule::Visible);
+        assert_eq!(neighbours[seat].len(), 8);
+        assert!(neighbours[seat].iter().all(|other| layout.initial[*other]));
+
+        // The leftmost empty seat below would only see one empty seat
+        let layout = Layout::parse(".............\n.L.L.#.#.#.#.\n.............").unwrap();
+        assert_eq!(layout.neighbours(Rule::Visible)[0], vec![1]);
+        assert_eq!(layout.neighbours(Rule::Adjacent)[0], vec![]);
+    }
+
+    #[test]
+    fn oscillation() {
+        // With a tolerance of one, two neighbouring seats take turns filling up and emptying.
+        assert_eq!(
+            inner("LL", 1, Rule::Adjacent, |_| {}),
+            Ok(Outcome::Cycle {
+                start: 0,
+                period: 2
+            })
+        );
+    }
+
+    #[test]
+    fn bad_layouts() {
+        assert_eq!(Layout::parse("").err(), Some(Error::Empty));
+        assert_eq!(Layout::parse("\nL.").err(), Some(Error::Empty));
+        assert_eq!(
+            Layout::parse("L.\nL").err(),
+            Some(Error::Width {
+                line: 2,
+                width: 1,
+                expected: 2
+            })
+        );
+        assert_eq!(
+            Layout::parse("L.\nL.L").err(),
+            Some(Error::Width {
+                line: 2,
+                width: 3,
+                expected: 2
+            })
+        );
+        assert_eq!(
+            Layout::parse("L.\nLx").err(),
+            Some(Error::Cell {
+                line: 2,
+                column: 2,
+                character: 'x'
+            })
+        );
     }
 }
@@ -0,0 +1,195 @@
+// Ways to watch the seating area settle, one generation at a time.
+//
+//     cargo run --bin day_11 -- 2 --text --stats --gif seats.gif
+
+use crate::Frame;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cell {
+    Floor,
+    Empty,
+    Occupied,
+}
+
+impl Cell {
+    fn to_char(self) -> char {
+        match self {
+            Cell::Floor => '.',
+            Cell::Empty => 'L',
+            Cell::Occupied => '#',
+        }
+    }
+}
+
+// The frame as it'd appear in the puzzle description.
+pub fn text(frame: &Frame) -> String {
+    let mut out = String::with_capacity((frame.layout.width + 1) * frame.layout.height);
+
+    for row in frame.rows() {
+        out.extend(row.map(Cell::to_char));
+        out.push('\n');
+    }
+
+    out
+}
+
+pub fn stats(frame: &Frame) -> String {
+    format!(
+        "Generation {}: {} seats changed, {} occupied",
+        frame.generation,
+        frame.changed,
+        frame.occupied.iter().filter(|o| **o).count()
+    )
+}
+
+// An animated GIF with one frame per generation, each cell drawn as a `scale` pixel square.
+pub struct Gif {
+    encoder: gif::Encoder<BufWriter<File>>,
+    scale: usize,
+}
+
+// Floor, empty and occupied.
+#[rustfmt::skip]
+const PALETTE: [u8; 9] = [
+    0x20, 0x20, 0x20,
+    0x80, 0xc0, 0xff,
+    0xff, 0x40, 0x40,
+];
+
+// Hundredths of a second between generations.
+const DELAY: u16 = 20;
+
+impl Gif {
+    pub fn create(path: &str, frame: &Frame, scale: usize) -> io::Result<Self> {
+        let width = frame.layout.width * scale;
+        let height = frame.layout.height * scale;
+        if width > u16::MAX as usize || height > u16::MAX as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "layout too large",
+            ));
+        }
+
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder =
+            gif::Encoder::new(file, width as u16, height as u16, &PALETTE).map_err(to_io)?;
+        encoder.set_repeat(gif::Repeat::Infinite).map_err(to_io)?;
+
+        Ok(Self { encoder, scale })
+    }
+
+    pub fn write(&mut self, frame: &Frame) -> io::Result<()> {
+        let width = frame.layout.width * self.scale;
+        let height = frame.layout.height * self.scale;
+
+        let mut pixels = Vec::with_capacity(width * height);
+        for row in frame.rows() {
+            let mut line = Vec::with_capacity(width);
+            for cell in row {
+                let index = match cell {
+                    Cell::Floor => 0,
+                    Cell::Empty => 1,
+                    Cell::Occupied => 2,
+                };
+                line.extend(std::iter::repeat_n(index, self.scale));
+            }
+            for _ in 0..self.scale {
+                pixels.extend_from_slice(&line);
+            }
+        }
+
+        let mut image = gif::Frame::from_indexed_pixels(width as u16, height as u16, pixels, None);
+        image.delay = DELAY;
+        self.encoder.write_frame(&image).map_err(to_io)?;
+        self.encoder.get_mut().flush()
+    }
+}
+
+fn to_io(error: gif::EncodingError) -> io::Error {
+    match error {
+        gif::EncodingError::Io(error) => error,
+        error => io::Error::other(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{inner, Rule};
+
+    #[test]
+    fn frames() {
+        let input = "L.LL
+L.LL
+....";
+
+        let mut texts = Vec::new();
+        let mut lines = Vec::new();
+        inner(input, 4, Rule::Adjacent, |frame| {
+            texts.push(text(frame));
+            lines.push(stats(frame));
+        })
+        .unwrap();
+
+        assert_eq!(texts, vec!["L.LL\nL.LL\n....\n", "#.##\n#.##\n....\n"]);
+        assert_eq!(
+            lines,
+            vec![
+                "Generation 0: 0 seats changed, 0 occupied",
+                "Generation 1: 6 seats changed, 6 occupied",
+            ]
+        );
+    }
+
+    #[test]
+    fn gif() {
+        let input = "L.LL.LL.LL
+LLLLLLL.LL
+L.L.L..L..
+LLLL.LL.LL
+L.LL.LL.LL
+L.LLLLL.LL
+..L.L.....
+LLLLLLLLLL
+L.LLLLLL.L
+L.LLLLL.LL";
+        let path = std::env::temp_dir().join(format!("day_11_{}.gif", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        let mut gif = None;
+        inner(input, 4, Rule::Adjacent, |frame| {
+            let gif = gif.get_or_insert_with(|| Gif::create(path, frame, 3).unwrap());
+            gif.write(frame).unwrap();
+        })
+        .unwrap();
+        // Dropping the encoder writes the trailer.
+        drop(gif);
+
+        let bytes = std::fs::read(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(&bytes[..6], b"GIF89a");
+
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::Indexed);
+        let mut decoder = options.read_info(&bytes[..]).unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (30, 30));
+        // GIF palettes hold a power of two colours, so ours is padded to 4.
+        assert_eq!(&decoder.global_palette().unwrap()[..9], &PALETTE[..]);
+
+        let mut frames = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!((frame.width, frame.height, frame.delay), (30, 30, DELAY));
+            frames.push(frame.buffer.to_vec());
+        }
+        // The starting layout and the 5 generations it takes to settle.
+        assert_eq!(frames.len(), 6);
+
+        // The top left seat starts empty and is filled in the next generation, and each cell is a
+        // 3 pixel square.
+        assert_eq!(&frames[0][..6], &[1, 1, 1, 0, 0, 0]);
+        assert_eq!(&frames[1][..6], &[2, 2, 2, 0, 0, 0]);
+        assert_eq!(&frames[1][2 * 30..2 * 30 + 6], &[2, 2, 2, 0, 0, 0]);
+    }
+}
@@ -92,39 +92,98 @@
 // Figure out where the navigation instructions actually lead. What is the Manhattan distance
 // between that location and the ship's starting position?
 
+mod svg;
+
 use std::convert::TryInto;
+use std::fmt;
 
 fn main() {
     let input = include_str!("../input");
 
-    let part_1 = part_1(&input);
+    // cargo run --bin day_12 -- <ship|waypoint> [instructions file]
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        match plot(input, &args) {
+            Ok(svg) => print!("{}", svg),
+            Err(error) => {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let part_1 = part_1(input);
     assert_eq!(part_1, 1319);
     println!("Part 1: {}", part_1);
 
-    let part_2 = part_2(&input);
+    let part_2 = part_2(input);
     assert_eq!(part_2, 62434);
     println!("Part 2: {}", part_2);
 }
 
+fn plot(input: &str, args: &[String]) -> Result<String, String> {
+    let file;
+    let input = match args.get(1) {
+        Some(path) => {
+            file = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+            &file
+        }
+        None => input,
+    };
+    let actions = parse(input).map_err(|error| error.to_string())?;
+
+    match args[0].as_str() {
+        "ship" => Ok(svg::ship_route(&route(&actions))),
+        "waypoint" => Ok(svg::waypoint_route(
+            &route_with_waypoint(&actions),
+            &actions,
+        )),
+        _ => Err("usage: day_12 <ship|waypoint> [instructions file]".to_owned()),
+    }
+}
+
 fn part_1(input: &str) -> usize {
-    parse(input)
-        .iter()
-        .fold(Ship::new(), handle_action)
-        .manhattan_distance()
+    let actions = parse(input).unwrap_or_else(|error| panic!("{}", error));
+
+    route(&actions).last().unwrap().manhattan_distance()
 }
 
 fn part_2(input: &str) -> usize {
-    // 10 units east and 1 unit north
-    let waypoint = Waypoint::new(10, -1);
-
-    let (ship, _waypoint) = parse(input)
-        .iter()
-        .fold((Ship::new(), waypoint), handle_action_with_waypoint);
+    let actions = parse(input).unwrap_or_else(|error| panic!("{}", error));
 
+    let (ship, _waypoint) = route_with_waypoint(&actions).pop().unwrap();
     ship.manhattan_distance()
 }
 
-#[derive(Debug)]
+// Where the ship is after each action, starting at the origin.
+fn route(actions: &[Action]) -> Vec<Ship> {
+    let mut ship = Ship::new();
+    let mut route = vec![ship.clone()];
+
+    for action in actions {
+        ship = handle_action(ship, action);
+        route.push(ship.clone());
+    }
+
+    route
+}
+
+// Where the ship and waypoint are after each action, starting at the origin with the waypoint 10
+// units east and 1 unit north.
+fn route_with_waypoint(actions: &[Action]) -> Vec<(Ship, Waypoint)> {
+    let mut state = (Ship::new(), Waypoint::new(10, -1));
+    let mut route = vec![state.clone()];
+
+    for action in actions {
+        state = handle_action_with_waypoint(state, action);
+        route.push(state.clone());
+    }
+
+    route
+}
+
+#[derive(Debug, Clone, PartialEq)]
 struct Waypoint {
     x: isize,
     y: isize,
@@ -135,18 +194,18 @@ impl Waypoint {
         Self { x, y }
     }
 
-    fn right(&mut self, degrees: usize) {
+    fn right(&mut self, quarter_turns: usize) {
         let (x, y) = (0..)
-            .take(degrees / 90)
+            .take(quarter_turns)
             .fold((self.x, self.y), |(x, y), _| (-y, x));
 
         self.x = x;
         self.y = y;
     }
 
-    fn left(&mut self, degrees: usize) {
+    fn left(&mut self, quarter_turns: usize) {
         let (x, y) = (0..)
-            .take(degrees / 90)
+            .take(quarter_turns)
             .fold((self.x, self.y), |(x, y), _| (y, -x));
 
         self.x = x;
@@ -154,7 +213,7 @@ impl Waypoint {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 struct Ship {
     x: isize,
     y: isize,
@@ -167,8 +226,8 @@ fn handle_action(mut ship: Ship, action: &Action) -> Ship {
         Action::MoveSouth(amount) => ship.y += amount,
         Action::MoveEast(amount) => ship.x += amount,
         Action::MoveWest(amount) => ship.x -= amount,
-        Action::TurnLeft(degrees) => ship.direction.left(*degrees),
-        Action::TurnRight(degrees) => ship.direction.right(*degrees),
+        Action::TurnLeft(quarter_turns) => ship.direction.left(*quarter_turns),
+        Action::TurnRight(quarter_turns) => ship.direction.right(*quarter_turns),
         Action::MoveForward(amount) => match ship.direction {
             Direction::N => ship.y -= amount,
             Direction::E => ship.x += amount,
@@ -188,8 +247,8 @@ fn handle_action_with_waypoint(input: (Ship, Waypoint), action: &Action) -> (Shi
         Action::MoveSouth(amount) => waypoint.y += amount,
         Action::MoveEast(amount) => waypoint.x += amount,
         Action::MoveWest(amount) => waypoint.x -= amount,
-        Action::TurnLeft(degrees) => waypoint.left(*degrees),
-        Action::TurnRight(degrees) => waypoint.right(*degrees),
+        Action::TurnLeft(quarter_turns) => waypoint.left(*quarter_turns),
+        Action::TurnRight(quarter_turns) => waypoint.right(*quarter_turns),
         Action::MoveForward(amount) => {
             ship.x += amount * waypoint.x;
             ship.y += amount * waypoint.y;
@@ -213,7 +272,7 @@ impl Ship {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum Direction {
     N,
     S,
@@ -226,12 +285,16 @@ impl Direction {
         DirectionIter(*self)
     }
 
-    fn right(&mut self, degrees: usize) {
-        *self = self.iter().take(degrees / 90).last().unwrap();
+    fn right(&mut self, quarter_turns: usize) {
+        if let Some(direction) = self.iter().take(quarter_turns).last() {
+            *self = direction;
+        }
     }
 
-    fn left(&mut self, degrees: usize) {
-        *self = self.iter().rev().take(degrees / 90).last().unwrap();
+    fn left(&mut self, quarter_turns: usize) {
+        if let Some(direction) = self.iter().rev().take(quarter_turns).last() {
+            *self = direction;
+        }
     }
 }
 
@@ -265,34 +328,91 @@ impl DoubleEndedIterator for DirectionIter {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 enum Action {
     MoveNorth(isize),
     MoveSouth(isize),
     MoveEast(isize),
     MoveWest(isize),
+    // Turns are a whole number of quarter turns; the ship only ever faces north, south, east or
+    // west, and the waypoint stays on the grid.
     TurnLeft(usize),
     TurnRight(usize),
     MoveForward(isize),
 }
 
-fn parse(input: &str) -> Vec<Action> {
+#[derive(Debug, PartialEq)]
+enum ParseError {
+    Action { line: usize, action: String },
+    Value { line: usize, value: String },
+    // Only multiples of 90 degrees are supported.
+    Turn { line: usize, degrees: isize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Action { line, action } => {
+                write!(f, "line {}: invalid action {:?}", line, action)
+            }
+            ParseError::Value { line, value } => {
+                write!(f, "line {}: invalid value {:?}", line, value)
+            }
+            ParseError::Turn { line, degrees } => write!(
+                f,
+                "line {}: can't turn {} degrees, only multiples of 90",
+                line, degrees
+            ),
+        }
+    }
+}
+
+fn parse(input: &str) -> Result<Vec<Action>, ParseError> {
     input
         .lines()
-        .map(|line| {
-            let (action, value) = line.split_at(1);
-            let value: isize = value.parse().unwrap();
-
-            match action {
+        .enumerate()
+        .map(|(i, line)| {
+            let line_number = i + 1;
+            let split = line
+                .char_indices()
+                .nth(1)
+                .map(|(i, _)| i)
+                .unwrap_or(line.len());
+            let (action, value) = line.split_at(split);
+            let value: isize = value.parse().map_err(|_| ParseError::Value {
+                line: line_number,
+                value: value.to_owned(),
+            })?;
+
+            // A negative turn is a turn the other way, so L-90 is the same as L270.
+            let quarter_turns = || -> Result<usize, ParseError> {
+                if value % 90 == 0 {
+                    Ok((value / 90).rem_euclid(4) as usize)
+                } else {
+                    Err(ParseError::Turn {
+                        line: line_number,
+                        degrees: value,
+                    })
+                }
+            };
+
+            let action = match action {
                 "N" => Action::MoveNorth(value),
                 "S" => Action::MoveSouth(value),
                 "E" => Action::MoveEast(value),
                 "W" => Action::MoveWest(value),
-                "L" => Action::TurnLeft(value.try_into().unwrap()),
-                "R" => Action::TurnRight(value.try_into().unwrap()),
+                "L" => Action::TurnLeft(quarter_turns()?),
+                "R" => Action::TurnRight(quarter_turns()?),
                 "F" => Action::MoveForward(value),
-                _ => panic!("invalid action"),
-            }
+                _ => {
+                    return Err(ParseError::Action {
+                        line: line_number,
+                        action: action.to_owned(),
+                    })
+                }
+            };
+
+            Ok(action)
         })
         .collect()
 }
@@ -309,7 +429,80 @@ F7
 R90
 F11";
 
-        assert_eq!(part_1(&input), 25);
-        assert_eq!(part_2(&input), 286);
+        assert_eq!(part_1(input), 25);
+        assert_eq!(part_2(input), 286);
+
+        let actions = parse(input).unwrap();
+
+        let positions: Vec<_> = route(&actions)
+            .iter()
+            .map(|ship| (ship.x, ship.y))
+            .collect();
+        assert_eq!(
+            positions,
+            vec![(0, 0), (10, 0), (10, -3), (17, -3), (17, -3), (17, 8)]
+        );
+
+        let positions: Vec<_> = route_with_waypoint(&actions)
+            .iter()
+            .map(|(ship, waypoint)| ((ship.x, ship.y), (waypoint.x, waypoint.y)))
+            .collect();
+        assert_eq!(
+            positions,
+            vec![
+                ((0, 0), (10, -1)),
+                ((100, -10), (10, -1)),
+                ((100, -10), (10, -4)),
+                ((170, -38), (10, -4)),
+                ((170, -38), (4, 10)),
+                ((214, 72), (4, 10)),
+            ]
+        );
+    }
+
+    #[test]
+    fn turns() {
+        let actions = parse("R270\nF1\nL450\nF1\nR0\nF1").unwrap();
+        let ship = route(&actions).pop().unwrap();
+        assert_eq!((ship.x, ship.y, ship.direction), (-2, -1, Direction::W));
+
+        let (_, waypoint) = route_with_waypoint(&parse("L180\nR360").unwrap())
+            .pop()
+            .unwrap();
+        assert_eq!(waypoint, Waypoint::new(-10, 1));
+
+        assert_eq!(
+            parse("F10\nR45"),
+            Err(ParseError::Turn {
+                line: 2,
+                degrees: 45
+            })
+        );
+        // Negative turns go the other way.
+        assert_eq!(parse("L-90"), Ok(vec![Action::TurnLeft(3)]));
+        assert_eq!(parse("R-450"), Ok(vec![Action::TurnRight(3)]));
+        let ship = route(&parse("L-90\nF1").unwrap()).pop().unwrap();
+        assert_eq!((ship.x, ship.y, ship.direction), (0, 1, Direction::S));
+        assert_eq!(
+            parse("L-45"),
+            Err(ParseError::Turn {
+                line: 1,
+                degrees: -45
+            })
+        );
+        assert_eq!(
+            parse("X1"),
+            Err(ParseError::Action {
+                line: 1,
+                action: "X".to_owned()
+            })
+        );
+        assert_eq!(
+            parse("F"),
+            Err(ParseError::Value {
+                line: 1,
+                value: "".to_owned()
+            })
+        );
     }
 }
@@ -0,0 +1,145 @@
+// SVG plots of the ship's route, for checking instruction files by eye.
+//
+//     cargo run --bin day_12 -- ship > ship.svg
+//     cargo run --bin day_12 -- waypoint path/to/instructions > waypoint.svg
+//
+// North is up and east is right. The origin and the final position are marked, and the final
+// position is labelled with its Manhattan distance. For the waypoint model, every F action also
+// shows the waypoint vector it followed.
+
+use crate::{Action, Ship, Waypoint};
+use std::fmt::Write;
+
+type Point = (isize, isize);
+
+pub fn ship_route(route: &[Ship]) -> String {
+    let path: Vec<_> = route.iter().map(|ship| (ship.x, ship.y)).collect();
+
+    plot(&path, &[])
+}
+
+pub fn waypoint_route(route: &[(Ship, Waypoint)], actions: &[Action]) -> String {
+    let path: Vec<_> = route.iter().map(|(ship, _)| (ship.x, ship.y)).collect();
+
+    // route[i] is the state before actions[i].
+    let vectors: Vec<_> = actions
+        .iter()
+        .zip(route)
+        .filter(|(action, _)| matches!(action, Action::MoveForward(_)))
+        .map(|(_, (ship, waypoint))| ((ship.x, ship.y), (ship.x + waypoint.x, ship.y + waypoint.y)))
+        .collect();
+
+    plot(&path, &vectors)
+}
+
+fn plot(path: &[Point], vectors: &[(Point, Point)]) -> String {
+    let points = path
+        .iter()
+        .chain(vectors.iter().map(|(_, to)| to))
+        .chain(std::iter::once(&(0, 0)));
+
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (0, 0, 0, 0);
+    for (x, y) in points {
+        min_x = min_x.min(*x);
+        min_y = min_y.min(*y);
+        max_x = max_x.max(*x);
+        max_y = max_y.max(*y);
+    }
+
+    // Leave room around the route for the markers and labels.
+    let size = (max_x - min_x).max(max_y - min_y).max(1);
+    let margin = size / 10 + 1;
+    let marker = size / 100 + 1;
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" font-size="{}">"#,
+        min_x - margin,
+        min_y - margin,
+        max_x - min_x + margin * 2,
+        max_y - min_y + margin * 2,
+        marker * 3,
+    )
+    .unwrap();
+
+    for (from, to) in vectors {
+        writeln!(
+            out,
+            r#"  <line class="waypoint" x1="{}" y1="{}" x2="{}" y2="{}" stroke="orange" stroke-width="1" vector-effect="non-scaling-stroke"/>"#,
+            from.0, from.1, to.0, to.1
+        )
+        .unwrap();
+    }
+
+    let points: Vec<_> = path.iter().map(|(x, y)| format!("{},{}", x, y)).collect();
+    writeln!(
+        out,
+        r#"  <polyline class="ship" points="{}" fill="none" stroke="steelblue" stroke-width="2" vector-effect="non-scaling-stroke"/>"#,
+        points.join(" ")
+    )
+    .unwrap();
+
+    writeln!(
+        out,
+        r#"  <circle class="origin" cx="0" cy="0" r="{}" fill="green"/>"#,
+        marker
+    )
+    .unwrap();
+    writeln!(out, r#"  <text x="{}" y="0">origin</text>"#, marker * 2).unwrap();
+
+    let (x, y) = *path.last().unwrap_or(&(0, 0));
+    writeln!(
+        out,
+        r#"  <circle class="end" cx="{}" cy="{}" r="{}" fill="red"/>"#,
+        x, y, marker
+    )
+    .unwrap();
+    writeln!(
+        out,
+        r#"  <text x="{}" y="{}">{}, {} (distance {})</text>"#,
+        x + marker * 2,
+        y,
+        x,
+        y,
+        x.abs() + y.abs()
+    )
+    .unwrap();
+
+    writeln!(out, "</svg>").unwrap();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse, route, route_with_waypoint};
+
+    #[test]
+    fn ship() {
+        let actions = parse("F10\nN3\nF7\nR90\nF11").unwrap();
+        let svg = ship_route(&route(&actions));
+
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="-2 -5 21 15""#));
+        assert!(svg.contains(r#"points="0,0 10,0 10,-3 17,-3 17,-3 17,8""#));
+        assert!(svg.contains(r#"<circle class="end" cx="17" cy="8" r="1" fill="red"/>"#));
+        assert!(svg.contains("17, 8 (distance 25)"));
+        assert!(!svg.contains("waypoint"));
+    }
+
+    #[test]
+    fn waypoint() {
+        let actions = parse("F10\nN3\nF7\nR90\nF11").unwrap();
+        let svg = waypoint_route(&route_with_waypoint(&actions), &actions);
+
+        let vectors: Vec<_> = svg
+            .lines()
+            .filter(|line| line.contains(r#"class="waypoint""#))
+            .collect();
+        assert_eq!(vectors.len(), 3);
+        assert!(vectors[0].contains(r#"x1="0" y1="0" x2="10" y2="-1""#));
+        assert!(vectors[1].contains(r#"x1="100" y1="-10" x2="110" y2="-14""#));
+        assert!(vectors[2].contains(r#"x1="170" y1="-38" x2="174" y2="-28""#));
+        assert!(svg.contains("214, 72 (distance 286)"));
+    }
+}
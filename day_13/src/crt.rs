@@ -0,0 +1,156 @@
+// A general solver for systems of congruences, x ≡ a (mod n). Unlike the textbook Chinese
+// remainder theorem the moduli don't need to be pairwise coprime: pairs are merged one at a time
+// using their gcd, and if two constraints can't both hold there is no solution.
+//
+// https://en.wikipedia.org/wiki/Chinese_remainder_theorem#Generalization_to_non-coprime_moduli
+
+use num_bigint::BigInt;
+use num_integer::Integer;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Congruence {
+    pub remainder: BigInt,
+    pub modulus: BigInt,
+}
+
+impl Congruence {
+    pub fn new(remainder: impl Into<BigInt>, modulus: impl Into<BigInt>) -> Self {
+        Self {
+            remainder: remainder.into(),
+            modulus: modulus.into(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CrtError {
+    // The modulus at this index isn't positive.
+    InvalidModulus(usize),
+    // The congruence at this index contradicts the ones before it.
+    NoSolution(usize),
+    // A solution exists but is too big for the caller's integer type.
+    Overflow(BigInt),
+}
+
+impl fmt::Display for CrtError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CrtError::InvalidModulus(i) => write!(f, "constraint {} has a modulus below 1", i),
+            CrtError::NoSolution(i) => {
+                write!(f, "no solution, constraint {} conflicts with the others", i)
+            }
+            CrtError::Overflow(solution) => write!(f, "the solution {} is too big", solution),
+        }
+    }
+}
+
+// The combined constraint: every solution is `remainder` plus a multiple of `modulus`, with
+// `remainder` the smallest non-negative one. No constraints at all gives x ≡ 0 (mod 1).
+pub fn solve(congruences: &[Congruence]) -> Result<Congruence, CrtError> {
+    congruences
+        .iter()
+        .enumerate()
+        .try_fold(Congruence::new(0, 1), |acc, (i, next)| {
+            if next.modulus < BigInt::from(1) {
+                return Err(CrtError::InvalidModulus(i));
+            }
+            merge(&acc, next).ok_or(CrtError::NoSolution(i))
+        })
+}
+
+fn merge(a: &Congruence, b: &Congruence) -> Option<Congruence> {
+    // x = a.remainder + a.modulus * k, so we need a.modulus * k ≡ b.remainder - a.remainder
+    // (mod b.modulus), which is only solvable if the gcd divides the difference.
+    let (g, p, _) = extended_gcd(&a.modulus, &b.modulus);
+    let diff = &b.remainder - &a.remainder;
+    if &diff % &g != BigInt::from(0) {
+        return None;
+    }
+
+    let modulus = &a.modulus / &g * &b.modulus;
+    let step = &b.modulus / &g;
+    let k = (diff / &g * p).mod_floor(&step);
+    let remainder = (&a.remainder + &a.modulus * k).mod_floor(&modulus);
+
+    Some(Congruence { remainder, modulus })
+}
+
+// Returns (g, x, y) where g = gcd(a, b) = a * x + b * y.
+fn extended_gcd(a: &BigInt, b: &BigInt) -> (BigInt, BigInt, BigInt) {
+    let (mut old_r, mut r) = (a.clone(), b.clone());
+    let (mut old_s, mut s) = (BigInt::from(1), BigInt::from(0));
+    let (mut old_t, mut t) = (BigInt::from(0), BigInt::from(1));
+
+    while r != BigInt::from(0) {
+        let q = &old_r / &r;
+        let next_r = &old_r - &q * &r;
+        old_r = std::mem::replace(&mut r, next_r);
+        let next_s = &old_s - &q * &s;
+        old_s = std::mem::replace(&mut s, next_s);
+        let next_t = &old_t - &q * &t;
+        old_t = std::mem::replace(&mut t, next_t);
+    }
+
+    (old_r, old_s, old_t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryInto;
+
+    fn solve_pairs(pairs: &[(i64, i64)]) -> Result<(i64, i64), CrtError> {
+        let congruences: Vec<_> = pairs.iter().map(|(a, n)| Congruence::new(*a, *n)).collect();
+        solve(&congruences).map(|c| {
+            (
+                c.remainder.try_into().unwrap(),
+                c.modulus.try_into().unwrap(),
+            )
+        })
+    }
+
+    #[test]
+    fn coprime() {
+        assert_eq!(solve_pairs(&[(0, 7), (12, 13)]), Ok((77, 91)));
+        assert_eq!(solve_pairs(&[(55, 59), (77, 91)]), Ok((350, 5369)));
+        assert_eq!(solve_pairs(&[(2, 3), (3, 5), (2, 7)]), Ok((23, 105)));
+    }
+
+    #[test]
+    fn any_number_of_constraints() {
+        assert_eq!(solve_pairs(&[]), Ok((0, 1)));
+        assert_eq!(solve_pairs(&[(-3, 7)]), Ok((4, 7)));
+    }
+
+    #[test]
+    fn non_coprime() {
+        assert_eq!(solve_pairs(&[(0, 6), (2, 4)]), Ok((6, 12)));
+        assert_eq!(solve_pairs(&[(3, 10), (7, 12), (13, 15)]), Ok((43, 60)));
+
+        // x can't be both even and odd.
+        assert_eq!(solve_pairs(&[(0, 4), (5, 6)]), Err(CrtError::NoSolution(1)));
+        assert_eq!(
+            solve_pairs(&[(1, 3), (0, 0)]),
+            Err(CrtError::InvalidModulus(1))
+        );
+    }
+
+    #[test]
+    fn large_moduli() {
+        // The product of these primes doesn't fit in an i128.
+        let primes = [
+            1_000_000_007i64,
+            1_000_000_009,
+            998_244_353,
+            1_000_000_021,
+            1_000_000_033,
+        ];
+        let congruences: Vec<_> = primes.iter().map(|p| Congruence::new(p - 1, *p)).collect();
+        let solution = solve(&congruences).unwrap();
+
+        // -1 is a solution to every constraint.
+        assert_eq!(solution.remainder, &solution.modulus - 1);
+        assert!(solution.modulus > BigInt::from(i128::MAX));
+    }
+}
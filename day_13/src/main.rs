@@ -137,78 +137,119 @@
 // What is the earliest timestamp such that all of the listed bus IDs depart at offsets matching
 // their positions in the list?
 
+mod crt;
+mod timetable;
+
+use crt::CrtError;
+use timetable::{Timetable, ZeroBus};
+
 fn main() {
     let input = include_str!("../input");
 
-    let part_1 = part_1(&input);
+    // cargo run --bin day_13 -- <query> [arguments]
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        match query(input, &args) {
+            Ok(output) => print!("{}", output),
+            Err(error) => {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let part_1 = part_1(input).unwrap_or_else(|error| panic!("{}", error));
     assert_eq!(part_1, 115);
     println!("Part 1: {}", part_1);
 
-    let part_2 = part_2(&input);
+    let part_2 = part_2(input);
     assert_eq!(part_2, 756261495958122);
     println!("Part 2: {}", part_2);
 }
 
-fn part_1(input: &str) -> usize {
+fn part_1(input: &str) -> Result<usize, ZeroBus> {
     let (earliest_departure, services) = parse_input(input);
-    let (next_service, next_departure) = next_service(earliest_departure, &services);
+    let (next_service, next_departure) = next_service(earliest_departure, &services)?;
 
-    (next_departure - earliest_departure) * next_service
+    Ok((next_departure - earliest_departure) * next_service)
 }
 
 fn part_2(input: &str) -> usize {
     let (_earliest_departure, services) = parse_input(input);
-    part_2_inner(&services)
-}
-
-// https://en.wikipedia.org/wiki/Chinese_remainder_theorem
-fn chinese_remainder_theorem(a1: i128, a2: i128, n1: i128, n2: i128) -> i128 {
-    let (_, m1, _) = modinverse::egcd(n1, n2);
-    (a1 + (a2 - a1) * m1 * n1).rem_euclid(n1 * n2)
+    part_2_inner(&services).unwrap_or_else(|error| panic!("{}", error))
 }
 
-fn lst_to_dict(services: &[Service]) -> Vec<(i128, i128)> {
+// Each bus with its offset in the list.
+fn pattern(services: &[Service]) -> Vec<(usize, usize)> {
     services
         .iter()
         .enumerate()
-        .filter_map(|(i, s)| match s {
-            Service::WithTime(t) => Some((i, *t)),
+        .filter_map(|(offset, service)| match service {
+            Service::WithTime(bus) => Some((*bus, offset)),
             Service::AnyTime => None,
         })
-        // c = offset in list
-        // n1 = bus number
-        .map(|(c, n1)| {
-            let c = c as i128;
-            let n1 = n1 as i128;
-            let a1 = (n1 - c) * if c > 0 { 1 } else { 0 } % n1;
-            (n1, a1)
-        })
         .collect()
 }
 
-fn part_2_inner(services: &[Service]) -> usize {
-    let list = lst_to_dict(services);
-
-    // Initialize
-    let (mut n1, mut a1) = list[0];
-    let (mut n2, mut a2) = list[1];
-
-    let mut rem = chinese_remainder_theorem(a1, a2, n1, n2);
+fn part_2_inner(services: &[Service]) -> Result<usize, CrtError> {
+    timetable::earliest_matching(&pattern(services))
+}
 
-    // Skip 2 (handled above)
-    for (new_n1, new_a1) in list.iter().skip(2) {
-        // Increment product
-        n2 = n1 * n2;
+const USAGE: &str = "usage:
+    day_13 next <time>
+    day_13 together <count> [time]
+    day_13 schedule <from> <to>
+    day_13 pattern <bus>:<offset>,...";
 
-        a2 = rem;
+fn query(input: &str, args: &[String]) -> Result<String, String> {
+    let (earliest_departure, services) = parse_input(input);
+    let buses: Vec<_> = pattern(&services).into_iter().map(|(bus, _)| bus).collect();
+    let timetable = Timetable::new(&buses).map_err(|e| e.to_string())?;
 
-        a1 = *new_a1;
-        n1 = *new_n1;
+    let number = |arg: &String| -> Result<usize, String> {
+        arg.parse().map_err(|_| format!("not a number: {}", arg))
+    };
 
-        rem = chinese_remainder_theorem(a1, a2, n1, n2);
+    let mut out = String::new();
+    match args {
+        [query, time] if query == "next" => {
+            for (bus, time) in timetable.next_departures(number(time)?) {
+                out += &format!("bus {}: {}\n", bus, time);
+            }
+        }
+        [query, count, rest @ ..] if query == "together" && rest.len() <= 1 => {
+            let time = match rest.first() {
+                Some(time) => number(time)?,
+                None => earliest_departure,
+            };
+            match timetable.next_together(number(count)?, time) {
+                Some((time, buses)) => out += &format!("{}: {:?}\n", time, buses),
+                None => return Err(format!("there aren't {} buses", count)),
+            }
+        }
+        [query, from, to] if query == "schedule" => {
+            for (time, buses) in timetable.schedule(number(from)?, number(to)?) {
+                out += &format!("{}: {:?}\n", time, buses);
+            }
+        }
+        [query, pattern] if query == "pattern" => {
+            let pattern = pattern
+                .split(',')
+                .map(|pair| {
+                    let mut iter = pair.splitn(2, ':');
+                    let bus = iter.next().unwrap().to_owned();
+                    let offset = iter.next().ok_or(USAGE)?.to_owned();
+                    Ok((number(&bus)?, number(&offset)?))
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            let time = timetable::earliest_matching(&pattern).map_err(|e| e.to_string())?;
+            out += &format!("{}\n", time);
+        }
+        _ => return Err(USAGE.to_owned()),
     }
 
-    rem as _
+    Ok(out)
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -241,32 +282,20 @@ fn parse_input(input: &str) -> (usize, Vec<Service>) {
     (earliest_departure, services)
 }
 
-fn next_service(earliest_departure: usize, services: &[Service]) -> (usize, usize) {
-    services
-        .iter()
-        .filter_map(|&service| {
-            match service {
-                Service::WithTime(service) => {
-                    // Find the first timestamp after the earliest departure
-                    let timestamp = (1..)
-                        .find_map(|i| {
-                            let timestamp = i * service;
-                            if timestamp > earliest_departure {
-                                Some(timestamp)
-                            } else {
-                                None
-                            }
-                        })
-                        .unwrap();
-
-                    Some((service, timestamp))
-                }
-                Service::AnyTime => None,
-            }
-        })
+// The first bus to depart at or after `earliest_departure`. A bus departing at exactly that time
+// still counts, as you're there when it leaves, so the wait can be 0.
+fn next_service(
+    earliest_departure: usize,
+    services: &[Service],
+) -> Result<(usize, usize), ZeroBus> {
+    let buses: Vec<_> = pattern(services).into_iter().map(|(bus, _)| bus).collect();
+
+    Ok(Timetable::new(&buses)?
+        .next_departures(earliest_departure)
+        .into_iter()
         // Find the earliest timestamp
         .min_by_key(|x| x.1)
-        .unwrap()
+        .unwrap())
 }
 
 #[cfg(test)]
@@ -289,17 +318,47 @@ mod tests {
     #[test]
     fn readme_example_part_1() {
         let earliest_departure = 939;
-        assert_eq!(next_service(earliest_departure, &services()), (59, 944));
+        assert_eq!(next_service(earliest_departure, &services()), Ok((59, 944)));
+
+        // Bus 59 departs at 944, so there's no wait.
+        assert_eq!(next_service(944, &services()), Ok((59, 944)));
+        assert_eq!(part_1("944\n7,13,x,x,59,x,31,19"), Ok(0));
+
+        assert_eq!(part_1("10\n0,7"), Err(ZeroBus));
     }
 
     #[test]
     fn readme_example_part_2() {
-        assert_eq!(part_2_inner(&services()), 1068781);
+        assert_eq!(part_2_inner(&services()), Ok(1068781));
     }
 
     #[test]
-    fn test_chi_rem() {
-        assert_eq!(chinese_remainder_theorem(0, 12, 7, 13), 77);
-        assert_eq!(chinese_remainder_theorem(55, 77, 59, 91), 350);
+    fn readme_examples_part_2() {
+        let examples = [
+            ("17,x,13,19", 3417),
+            ("67,7,59,61", 754018),
+            ("67,x,7,59,61", 779210),
+            ("67,7,x,59,61", 1261476),
+            ("1789,37,47,1889", 1202161486),
+        ];
+
+        for (list, timestamp) in examples.iter() {
+            let (_, services) = parse_input(&format!("0\n{}", list));
+            assert_eq!(part_2_inner(&services), Ok(*timestamp));
+        }
+    }
+
+    #[test]
+    fn any_number_of_buses() {
+        let (_, services) = parse_input("0\nx,x,7");
+        assert_eq!(part_2_inner(&services), Ok(5));
+
+        // Bus 4 leaves on even minutes, so bus 6 can't leave a minute later.
+        let (_, services) = parse_input("0\n4,6");
+        assert_eq!(part_2_inner(&services), Err(CrtError::NoSolution(1)));
+
+        // 6 and 4 share a factor, but t = 6 works: 6 + 2 = 8.
+        let (_, services) = parse_input("0\n6,x,4");
+        assert_eq!(part_2_inner(&services), Ok(6));
     }
 }
@@ -0,0 +1,212 @@
+// Queries over the bus timetable. Bus n departs at every multiple of n, so the next departure at
+// or after t is t rounded up to a multiple of n.
+//
+//     cargo run --bin day_13 -- next 939
+//     cargo run --bin day_13 -- together 3 1000000
+//     cargo run --bin day_13 -- schedule 939 950
+//     cargo run --bin day_13 -- pattern 17:0,13:2,19:3
+
+use crate::crt::{self, Congruence, CrtError};
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+use std::fmt;
+
+pub struct Timetable {
+    buses: Vec<usize>,
+}
+
+// Bus 0 would depart every 0 minutes, which isn't a timetable.
+#[derive(Debug, PartialEq)]
+pub struct ZeroBus;
+
+impl fmt::Display for ZeroBus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "bus 0 never departs")
+    }
+}
+
+impl Timetable {
+    pub fn new(buses: &[usize]) -> Result<Self, ZeroBus> {
+        if buses.contains(&0) {
+            return Err(ZeroBus);
+        }
+
+        let mut buses = buses.to_vec();
+        buses.sort_unstable();
+        buses.dedup();
+
+        Ok(Self { buses })
+    }
+
+    // The first departure of `bus` at or after `t`.
+    pub fn next_departure(bus: usize, t: usize) -> usize {
+        t.div_ceil(bus) * bus
+    }
+
+    // The next departure of each bus at or after `t`, as (bus, time).
+    pub fn next_departures(&self, t: usize) -> Vec<(usize, usize)> {
+        self.buses
+            .iter()
+            .map(|bus| (*bus, Self::next_departure(*bus, t)))
+            .collect()
+    }
+
+    // The first time at or after `t` when at least `k` buses depart together, and which buses
+    // they are. k buses depart together at multiples of their lcm, so this tries every choice of
+    // k buses.
+    pub fn next_together(&self, k: usize, t: usize) -> Option<(usize, Vec<usize>)> {
+        if k == 0 || k > self.buses.len() {
+            return None;
+        }
+
+        let mut best: Option<usize> = None;
+        combinations(&self.buses, k, &mut Vec::new(), &mut |buses| {
+            let time = buses
+                .iter()
+                .try_fold(1, |acc, bus| lcm(acc, *bus))
+                .and_then(|lcm| t.div_ceil(lcm).checked_mul(lcm));
+
+            if let Some(time) = time {
+                if best.is_none_or(|best| time < best) {
+                    best = Some(time);
+                }
+            }
+        });
+
+        // More than k buses might leave at that time.
+        best.map(|time| (time, self.departing_at(time)))
+    }
+
+    // Every departure from `from` to `to` inclusive, as (time, buses), in time order.
+    pub fn schedule(&self, from: usize, to: usize) -> Vec<(usize, Vec<usize>)> {
+        let mut schedule: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+
+        for bus in &self.buses {
+            let first = Self::next_departure(*bus, from);
+            for time in (first..=to).step_by(*bus) {
+                schedule.entry(time).or_default().push(*bus);
+            }
+        }
+
+        schedule.into_iter().collect()
+    }
+
+    fn departing_at(&self, time: usize) -> Vec<usize> {
+        self.buses
+            .iter()
+            .copied()
+            .filter(|bus| time.is_multiple_of(*bus))
+            .collect()
+    }
+}
+
+// The earliest time t where each (bus, offset) in the pattern departs at t + offset.
+pub fn earliest_matching(pattern: &[(usize, usize)]) -> Result<usize, CrtError> {
+    // t + offset ≡ 0 (mod bus), so t ≡ -offset (mod bus).
+    let congruences: Vec<_> = pattern
+        .iter()
+        .map(|(bus, offset)| Congruence::new(-(*offset as i128), *bus as u64))
+        .collect();
+    let solution = crt::solve(&congruences)?;
+
+    (&solution.remainder)
+        .try_into()
+        .map_err(|_| CrtError::Overflow(solution.remainder.clone()))
+}
+
+fn combinations<F>(items: &[usize], k: usize, chosen: &mut Vec<usize>, f: &mut F)
+where
+    F: FnMut(&[usize]),
+{
+    if chosen.len() == k {
+        f(chosen);
+        return;
+    }
+
+    for (i, item) in items.iter().enumerate() {
+        chosen.push(*item);
+        combinations(&items[i + 1..], k, chosen, f);
+        chosen.pop();
+    }
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn lcm(a: usize, b: usize) -> Option<usize> {
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timetable() -> Timetable {
+        Timetable::new(&[7, 13, 59, 31, 19]).unwrap()
+    }
+
+    #[test]
+    fn next_departures() {
+        assert_eq!(
+            timetable().next_departures(939),
+            vec![(7, 945), (13, 949), (19, 950), (31, 961), (59, 944)]
+        );
+        // A bus departing at exactly t is the next departure.
+        assert_eq!(Timetable::next_departure(7, 945), 945);
+        assert_eq!(Timetable::next_departure(7, 0), 0);
+
+        assert!(matches!(Timetable::new(&[7, 0]), Err(ZeroBus)));
+    }
+
+    #[test]
+    fn together() {
+        let timetable = Timetable::new(&[2, 3, 5]).unwrap();
+
+        assert_eq!(timetable.next_together(1, 7), Some((8, vec![2])));
+        assert_eq!(timetable.next_together(2, 7), Some((10, vec![2, 5])));
+        assert_eq!(timetable.next_together(2, 11), Some((12, vec![2, 3])));
+        assert_eq!(timetable.next_together(3, 1), Some((30, vec![2, 3, 5])));
+        assert_eq!(timetable.next_together(4, 1), None);
+    }
+
+    #[test]
+    fn schedule() {
+        assert_eq!(
+            timetable().schedule(939, 950),
+            vec![
+                (944, vec![59]),
+                (945, vec![7]),
+                (949, vec![13]),
+                (950, vec![19])
+            ]
+        );
+        assert_eq!(
+            Timetable::new(&[2, 3]).unwrap().schedule(0, 6),
+            vec![
+                (0, vec![2, 3]),
+                (2, vec![2]),
+                (3, vec![3]),
+                (4, vec![2]),
+                (6, vec![2, 3])
+            ]
+        );
+    }
+
+    #[test]
+    fn patterns() {
+        // The list order, 17,x,13,19
+        assert_eq!(earliest_matching(&[(17, 0), (13, 2), (19, 3)]), Ok(3417));
+        // Any order and any offsets
+        assert_eq!(earliest_matching(&[(19, 3), (17, 0), (13, 2)]), Ok(3417));
+        assert_eq!(earliest_matching(&[(7, 10)]), Ok(4));
+        assert_eq!(earliest_matching(&[]), Ok(0));
+
+        let error = earliest_matching(&[(1000000007, 0), (1000000009, 1), (998244353, 2)]);
+        assert!(matches!(error, Err(CrtError::Overflow(_))));
+    }
+}
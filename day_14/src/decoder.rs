@@ -0,0 +1,186 @@
+// The version 2 decoder chip writes to every address matching a pattern, which is 2^k addresses
+// for k floating bits. Instead of expanding them, memory keeps a list of disjoint patterns with the
+// value last written to each. A new write carves its pattern out of every older one, so the older
+// patterns only ever cover the addresses that still hold their values.
+
+// An address pattern. Bits set in `floating` match either 0 or 1, the rest must equal `fixed`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pattern {
+    fixed: usize,
+    floating: usize,
+}
+
+impl Pattern {
+    pub fn new(fixed: usize, floating: usize) -> Self {
+        Self {
+            fixed: fixed & !floating,
+            floating,
+        }
+    }
+
+    // How many addresses the pattern matches, which is 2^64 when every bit of a 64 bit address
+    // floats.
+    pub fn len(&self) -> u128 {
+        1 << self.floating.count_ones()
+    }
+
+    // The addresses matched by both patterns, if there are any.
+    fn intersect(&self, other: &Pattern) -> Option<Pattern> {
+        let both_fixed = !self.floating & !other.floating;
+        if (self.fixed ^ other.fixed) & both_fixed != 0 {
+            return None;
+        }
+
+        Some(Pattern::new(
+            self.fixed | other.fixed,
+            self.floating & other.floating,
+        ))
+    }
+
+    // The addresses matched by this pattern but not `other`, as disjoint patterns. Each floating
+    // bit that the overlap pins down splits off the half that can't overlap, which leaves at most
+    // one piece per floating bit.
+    fn subtract(&self, other: &Pattern) -> Vec<Pattern> {
+        let overlap = match self.intersect(other) {
+            Some(overlap) => overlap,
+            None => return vec![*self],
+        };
+
+        let mut pieces = Vec::new();
+        let mut rest = *self;
+        let mut bits = self.floating & !overlap.floating;
+
+        while bits != 0 {
+            let bit = bits & bits.wrapping_neg();
+            bits &= bits - 1;
+
+            let floating = rest.floating & !bit;
+            pieces.push(Pattern::new(rest.fixed | (!overlap.fixed & bit), floating));
+            rest = Pattern::new(rest.fixed | (overlap.fixed & bit), floating);
+        }
+
+        // What's left of `rest` is the overlap itself, which is dropped.
+        pieces
+    }
+}
+
+#[derive(Default)]
+pub struct Memory {
+    // Disjoint patterns and the value held by each of their addresses.
+    writes: Vec<(Pattern, usize)>,
+}
+
+impl Memory {
+    pub fn write(&mut self, pattern: Pattern, value: usize) {
+        self.writes = self
+            .writes
+            .iter()
+            .flat_map(|(old, old_value)| {
+                old.subtract(&pattern)
+                    .into_iter()
+                    .map(move |piece| (piece, *old_value))
+            })
+            .collect();
+
+        self.writes.push((pattern, value));
+    }
+
+    // The sum of every value in memory. A value written to 2^36 addresses is already too big for
+    // a usize, so the sum is a u128.
+    pub fn sum(&self) -> u128 {
+        self.writes
+            .iter()
+            .map(|(pattern, value)| pattern.len() * *value as u128)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn addresses(pattern: &Pattern) -> Vec<usize> {
+        (0..pattern.len() as usize)
+            .map(|n| {
+                // Spread the bits of n over the floating bits.
+                let mut address = pattern.fixed;
+                let mut bits = pattern.floating;
+                let mut n = n;
+                while bits != 0 {
+                    let bit = bits & bits.wrapping_neg();
+                    bits &= bits - 1;
+                    if n & 1 == 1 {
+                        address |= bit;
+                    }
+                    n >>= 1;
+                }
+                address
+            })
+            .collect()
+    }
+
+    #[test]
+    fn subtract() {
+        // X1101X minus 1X0XX leaves 0110X and X11011, neither of which overlaps it.
+        let a = Pattern::new(0b011010, 0b100001);
+        let b = Pattern::new(0b010000, 0b001011);
+
+        let pieces = a.subtract(&b);
+        let mut remaining: Vec<_> = pieces.iter().flat_map(addresses).collect();
+        remaining.sort_unstable();
+        assert_eq!(remaining, vec![58, 59]);
+        assert_eq!(pieces.iter().map(Pattern::len).sum::<u128>(), 2);
+
+        let c = Pattern::new(0b000000, 0b000001);
+        assert_eq!(a.subtract(&c), vec![a]);
+        assert_eq!(c.subtract(&c), vec![]);
+    }
+
+    #[test]
+    fn matches_expanded_memory() {
+        let writes = [
+            (Pattern::new(0b0101_0000, 0b0000_1111), 3),
+            (Pattern::new(0b0000_0011, 0b1100_0100), 5),
+            (Pattern::new(0b0001_0000, 0b1110_1001), 7),
+            (Pattern::new(0b1111_1111, 0b0000_0000), 11),
+            (Pattern::new(0b0000_0000, 0b0011_1100), 13),
+        ];
+
+        let mut memory = Memory::default();
+        let mut expanded = HashMap::new();
+        for (pattern, value) in &writes {
+            memory.write(*pattern, *value);
+            for address in addresses(pattern) {
+                expanded.insert(address, *value);
+            }
+
+            assert_eq!(memory.sum(), expanded.values().map(|v| *v as u128).sum());
+        }
+    }
+
+    #[test]
+    fn many_floating_bits() {
+        let everything = Pattern::new(0, (1 << 36) - 1);
+
+        let mut memory = Memory::default();
+        memory.write(everything, 2);
+        memory.write(Pattern::new(1 << 35, (1 << 35) - 1), 1);
+        memory.write(Pattern::new(0, 0), 10);
+        assert_eq!(memory.sum(), (1 << 35) + ((1 << 35) - 1) * 2 + 10);
+
+        memory.write(everything, 3);
+        assert_eq!(memory.sum(), 3 << 36);
+
+        let largest = (1 << 36) - 1;
+        memory.write(everything, largest);
+        assert_eq!(memory.sum(), (largest as u128) << 36);
+
+        memory.write(Pattern::new(0, (1 << 30) - 1), largest);
+        memory.write(Pattern::new(1 << 35, (1 << 30) - 1), 1);
+        assert_eq!(
+            memory.sum(),
+            (largest as u128) * ((1 << 36) - (1 << 30)) + (1 << 30)
+        );
+    }
+}
@@ -127,9 +127,25 @@
 // Execute the initialization program using an emulator for a version 2 decoder chip. What is the
 // sum of all values left in memory after it completes?
 
+mod decoder;
+mod mask;
+
+use decoder::{Memory, Pattern};
+use mask::{Mask, MaskError, WIDTH};
 use std::collections::HashMap;
+use std::fmt;
 
 fn main() {
+    // cargo run --bin day_14 -- <width> <program file>
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        if let Err(error) = run(&args) {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let lines: Vec<_> = include_str!("../input").lines().collect();
 
     let part_1 = part_1(&lines);
@@ -141,181 +157,135 @@ fn main() {
     println!("Part 2: {}", part_2);
 }
 
-#[derive(Default)]
-struct State {
-    zer_mask: usize,
-    one_mask: usize,
-    mem: HashMap<usize, usize>,
+fn run(args: &[String]) -> Result<(), String> {
+    let (width, path) = match args {
+        [width, path] => (width, path),
+        _ => return Err("usage: day_14 <width> <program file>".to_owned()),
+    };
+    let width = width
+        .parse()
+        .map_err(|_| format!("not a number: {}", width))?;
+    let input = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    let lines: Vec<_> = input.lines().collect();
+    let program = parse_lines(&lines, width).map_err(|e| e.to_string())?;
+
+    println!("Part 1: {}", part_1_inner(&program));
+    println!("Part 2: {}", part_2_inner(&program));
+
+    Ok(())
 }
 
-impl State {
-    fn apply_bitmasks(&self, value: usize) -> usize {
-        (value & self.zer_mask) | self.one_mask
-    }
+fn part_1(lines: &[&str]) -> u128 {
+    part_1_inner(&parse_lines(lines, WIDTH).unwrap_or_else(|error| panic!("{}", error)))
 }
 
-fn part_1(lines: &[&str]) -> usize {
-    parse_lines(lines)
-        .iter()
-        .fold(State::default(), |mut state, line| {
-            match line {
-                Line::Mask(mask) => {
-                    let (zer_mask, one_mask) = create_bitmasks(mask);
-                    state.zer_mask = zer_mask;
-                    state.one_mask = one_mask;
-                }
-                Line::Mem(address, value) => {
-                    state.mem.insert(*address, state.apply_bitmasks(*value));
-                }
+fn part_1_inner(program: &[Line]) -> u128 {
+    let mut mask = None;
+    let mut mem = HashMap::new();
+
+    for line in program {
+        match line {
+            Line::Mask(new_mask) => mask = Some(*new_mask),
+            Line::Mem(address, value) => {
+                let value = mask.map_or(*value, |mask: Mask| mask.apply(*value));
+                mem.insert(*address, value);
             }
-            state
-        })
-        .mem
-        .values()
-        .sum()
+        }
+    }
+
+    // With 64 bit masks, a couple of values can overflow a usize.
+    mem.values().map(|value| *value as u128).sum()
 }
 
-#[derive(Default)]
-struct StatePart2 {
-    mask: String,
-    mem: HashMap<usize, usize>,
+fn part_2(lines: &[&str]) -> u128 {
+    part_2_inner(&parse_lines(lines, WIDTH).unwrap_or_else(|error| panic!("{}", error)))
 }
 
-fn part_2(lines: &[&str]) -> usize {
-    parse_lines(lines)
-        .iter()
-        .fold(StatePart2::default(), |mut state, line| {
-            match line {
-                Line::Mask(mask) => state.mask = mask.to_owned(),
-                Line::Mem(address, value) => {
-                    let mask = decode_memory_address(&state.mask, *address);
-                    for address in mask_to_addresses(mask) {
-                        state.mem.insert(address, *value);
-                    }
-                }
+fn part_2_inner(program: &[Line]) -> u128 {
+    let mut mask = None;
+    let mut mem = Memory::default();
+
+    for line in program {
+        match line {
+            Line::Mask(new_mask) => mask = Some(*new_mask),
+            Line::Mem(address, value) => {
+                // As in part 1, there's no masking before the first mask, so only the address
+                // itself is written.
+                let pattern = mask.map_or(Pattern::new(*address, 0), |mask: Mask| {
+                    mask.decode(*address)
+                });
+                mem.write(pattern, *value);
             }
-            state
-        })
-        .mem
-        .values()
-        .sum()
+        }
+    }
+
+    mem.sum()
 }
 
 enum Line {
-    Mask(String),
+    Mask(Mask),
     Mem(usize, usize),
 }
 
-fn parse_lines(lines: &[&str]) -> Vec<Line> {
-    lines
-        .iter()
-        .map(|line| {
-            match line.chars().nth(1).unwrap() {
-                // mask
-                //  ^
-                'a' => Line::Mask(line.split(" = ").nth(1).unwrap().to_string()),
-                // mem
-                //  ^
-                'e' => {
-                    let (index, value) = parse_mem_line(line);
-                    Line::Mem(index, value)
-                }
-                _ => panic!("bad line"),
-            }
-        })
-        .collect()
+#[derive(Debug, PartialEq)]
+struct ParseError {
+    line: usize,
+    error: LineError,
 }
 
-fn parse_mem_line(line: &&str) -> (usize, usize) {
-    let parts = line.split('[').last().unwrap();
-    let mut parts = parts.split(']');
-
-    let index = parts.next().unwrap().parse().unwrap();
-
-    let value = parts
-        .next()
-        .unwrap()
-        .split(" = ")
-        .last()
-        .unwrap()
-        .parse()
-        .unwrap();
-
-    (index, value)
+#[derive(Debug, PartialEq)]
+enum LineError {
+    Mask(MaskError),
+    // Not `mem[<address>] = <value>`.
+    Mem(String),
+    // Neither a mask nor a memory write.
+    Unknown(String),
 }
 
-fn create_bitmasks(mask: &str) -> (usize, usize) {
-    let mut zer_mask = 0;
-    let mut one_mask = 0;
-
-    for c in mask.chars() {
-        zer_mask <<= 1;
-        one_mask <<= 1;
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
 
-        match c {
-            'X' => zer_mask |= 1,
-            '1' => {
-                zer_mask |= 1;
-                one_mask |= 1;
-            }
-            '0' => {}
-            _ => panic!("bad char"),
+        match &self.error {
+            LineError::Mask(error) => write!(f, "{}", error),
+            LineError::Mem(line) => write!(f, "{:?} is not a memory write", line),
+            LineError::Unknown(line) => write!(f, "{:?} is not a mask or a memory write", line),
         }
     }
+}
 
-    (zer_mask, one_mask)
+fn parse_lines(lines: &[&str], width: u32) -> Result<Vec<Line>, ParseError> {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            parse_line(line, width).map_err(|error| ParseError {
+                line: index + 1,
+                error,
+            })
+        })
+        .collect()
 }
 
-// If the bitmask bit is 0, the corresponding memory address bit is unchanged.
-// If the bitmask bit is 1, the corresponding memory address bit is overwritten with 1.
-// If the bitmask bit is X, the corresponding memory address bit is floating.
-fn decode_memory_address(mask: &str, address: usize) -> String {
-    let mut result = String::new();
-
-    for (mask_c, addr_c) in mask.chars().zip(format!("{:036b}", address).chars()) {
-        match mask_c {
-            '0' => result.push(addr_c),
-            '1' => result.push('1'),
-            'X' => result.push('X'),
-            _ => panic!("bad mask"),
-        }
+fn parse_line(line: &str, width: u32) -> Result<Line, LineError> {
+    if let Some(mask) = line.strip_prefix("mask = ") {
+        Mask::parse(mask, width)
+            .map(Line::Mask)
+            .map_err(LineError::Mask)
+    } else if line.starts_with("mem") {
+        parse_mem_line(line)
+            .map(|(address, value)| Line::Mem(address, value))
+            .ok_or_else(|| LineError::Mem(line.to_owned()))
+    } else {
+        Err(LineError::Unknown(line.to_owned()))
     }
-
-    result
 }
 
-// 000000000000000000000000000000X1101X
-//                               |    |
-// 000000000000000000000000000000011010  (decimal 26)
-// 000000000000000000000000000000011011  (decimal 27)
-// 000000000000000000000000000000111010  (decimal 58)
-// 000000000000000000000000000000111011  (decimal 59)
-fn mask_to_addresses(mask: String) -> Vec<usize> {
-    let mut addresses = Vec::new();
-
-    let mut masks = Vec::new();
-    masks.push(mask);
-
-    while !masks.is_empty() {
-        let mask = masks.pop().unwrap();
-
-        if let Some(index) = mask.find('X') {
-            // If we find an X, add two more masks to the queue with
-            // the found X replaced with 1 and 0
-            let mut mask_zer = mask.clone();
-            mask_zer.replace_range(index..index + 1, "0");
-            masks.push(mask_zer);
-
-            let mut mask_one = mask;
-            mask_one.replace_range(index..index + 1, "1");
-            masks.push(mask_one);
-        } else {
-            // Otherwise, the address is finished and can be converted to an integer
-            addresses.push(usize::from_str_radix(&mask, 2).unwrap())
-        }
-    }
+// mem[<address>] = <value>
+fn parse_mem_line(line: &str) -> Option<(usize, usize)> {
+    let (address, value) = line.strip_prefix("mem[")?.split_once("] = ")?;
 
-    addresses
+    Some((address.parse().ok()?, value.parse().ok()?))
 }
 
 #[cfg(test)]
@@ -324,7 +294,7 @@ mod tests {
 
     #[test]
     fn readme_example_part_1() {
-        let input = vec![
+        let input = [
             "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X",
             "mem[8] = 11",
             "mem[7] = 101",
@@ -336,7 +306,7 @@ mod tests {
 
     #[test]
     fn readme_example_part_2() {
-        let input = vec![
+        let input = [
             "mask = 000000000000000000000000000000X1001X",
             "mem[42] = 100",
             "mask = 00000000000000000000000000000000X0XX",
@@ -347,40 +317,97 @@ mod tests {
     }
 
     #[test]
-    fn test_decode_memory_address() {
-        let address = 0b000000000000000000000000000000101010;
-        let mask = "000000000000000000000000000000X1001X".to_string();
-        let result = "000000000000000000000000000000X1101X".to_string();
+    fn mask_errors() {
+        let input = ["mask = X1X", "mem[8] = 11"];
+
+        assert_eq!(
+            parse_lines(&input, 3).map(|program| part_1_inner(&program)),
+            Ok(0b011)
+        );
+        assert_eq!(
+            parse_lines(&input, 4).err().unwrap().to_string(),
+            "line 1: expected 4 bits, got 3"
+        );
+    }
+
+    #[test]
+    fn memory_before_mask() {
+        let input = ["mem[8] = 11", "mask = X0X", "mem[7] = 3"];
+        let program = parse_lines(&input, 3).unwrap();
 
-        assert_eq!(decode_memory_address(&mask, address), result);
+        // mem[7] = 3 writes 1 under the mask, and to 4 addresses in part 2.
+        assert_eq!(part_1_inner(&program), 11 + 1);
+        assert_eq!(part_2_inner(&program), 11 + 4 * 3);
     }
 
     #[test]
-    fn test_mask_to_addresses() {
-        let mask = "000000000000000000000000000000X1101X".to_string();
+    fn line_errors() {
+        let error = |input: &[&str]| parse_lines(input, 3).err().unwrap().to_string();
 
         assert_eq!(
-            mask_to_addresses(mask),
-            vec![
-                0b000000000000000000000000000000111011, // (decimal 59)
-                0b000000000000000000000000000000111010, // (decimal 58)
-                0b000000000000000000000000000000011011, // (decimal 27)
-                0b000000000000000000000000000000011010, // (decimal 26)
-            ]
+            error(&["mask = X1X", "mem[x] = 1"]),
+            "line 2: \"mem[x] = 1\" is not a memory write"
+        );
+        assert_eq!(
+            error(&["mem[1] = "]),
+            "line 1: \"mem[1] = \" is not a memory write"
         );
+        assert_eq!(
+            error(&["m"]),
+            "line 1: \"m\" is not a mask or a memory write"
+        );
+        assert_eq!(error(&[""]), "line 1: \"\" is not a mask or a memory write");
     }
 
     #[test]
-    fn test_create_bitmasks() {
-        // value:  000000000000000000000000000000001011  (decimal 11)
-        // mask:   XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X
-        // result: 000000000000000000000000000001001001  (decimal 73)
+    fn large_values_everywhere() {
+        let input = [
+            "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX",
+            "mem[0] = 68719476735",
+        ];
 
-        let mask = "XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X";
+        assert_eq!(part_2(&input), 68719476735 << 36);
+    }
 
-        let (zer_mask, one_mask) = create_bitmasks(mask);
+    #[test]
+    fn every_bit_set() {
+        let mask = format!("mask = {}", "1".repeat(64));
+        let input = [mask.as_str(), "mem[0] = 0", "mem[1] = 0"];
+        let program = parse_lines(&input, 64).unwrap();
 
-        assert_eq!(zer_mask, 0b111111111111111111111111111111111101);
-        assert_eq!(one_mask, 0b000000000000000000000000000001000000);
+        assert_eq!(part_1_inner(&program), 2 * u64::MAX as u128);
+    }
+
+    #[test]
+    fn every_bit_floating() {
+        let mask = format!("mask = {}", "X".repeat(64));
+        let input = [mask.as_str(), "mem[0] = 5"];
+        let program = parse_lines(&input, 64).unwrap();
+
+        assert_eq!(part_2_inner(&program), 5 << 64);
+    }
+
+    #[test]
+    fn fixtures() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures");
+        let expected = std::fs::read_to_string(format!("{}/expected", dir)).unwrap();
+
+        for line in expected.lines() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let fields: Vec<_> = line.split_whitespace().collect();
+            let (name, part_1_answer, part_2_answer) = match fields.as_slice() {
+                [name, part_1, part_2] => (name, part_1.parse(), part_2.parse()),
+                _ => panic!("bad fixture line: {}", line),
+            };
+
+            let input = std::fs::read_to_string(format!("{}/{}", dir, name)).unwrap();
+            let lines: Vec<_> = input.lines().collect();
+
+            assert_eq!(Ok(part_1(&lines)), part_1_answer, "part 1 of {}", name);
+            assert_eq!(Ok(part_2(&lines)), part_2_answer, "part 2 of {}", name);
+        }
     }
 }
@@ -0,0 +1,147 @@
+// A bitmask such as "XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X", stored as one bitfield per kind of
+// bit. The leftmost character is the most significant bit, and masks can be any width from 1 to 64
+// bits rather than just the puzzle's 36.
+
+use crate::decoder::Pattern;
+use std::fmt;
+
+pub const WIDTH: u32 = 36;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mask {
+    zeros: usize,
+    ones: usize,
+    floating: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum MaskError {
+    Width(u32),
+    Length { expected: u32, actual: usize },
+    Bit { index: usize, character: char },
+}
+
+impl fmt::Display for MaskError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MaskError::Width(width) => write!(f, "masks can't be {} bits wide", width),
+            MaskError::Length { expected, actual } => {
+                write!(f, "expected {} bits, got {}", expected, actual)
+            }
+            MaskError::Bit { index, character } => {
+                write!(f, "bit {} is {:?}, not 0, 1 or X", index, character)
+            }
+        }
+    }
+}
+
+impl Mask {
+    pub fn parse(input: &str, width: u32) -> Result<Self, MaskError> {
+        if width == 0 || width > usize::BITS {
+            return Err(MaskError::Width(width));
+        }
+
+        let length = input.chars().count();
+        if length != width as usize {
+            return Err(MaskError::Length {
+                expected: width,
+                actual: length,
+            });
+        }
+
+        let mut mask = Mask {
+            zeros: 0,
+            ones: 0,
+            floating: 0,
+        };
+
+        for (index, character) in input.chars().enumerate() {
+            let bit = 1 << (length - 1 - index);
+            match character {
+                '0' => mask.zeros |= bit,
+                '1' => mask.ones |= bit,
+                'X' => mask.floating |= bit,
+                _ => return Err(MaskError::Bit { index, character }),
+            }
+        }
+
+        Ok(mask)
+    }
+
+    // Version 1: a 0 or 1 overwrites the bit in the value, an X leaves it unchanged.
+    pub fn apply(&self, value: usize) -> usize {
+        (value & self.floating) | self.ones
+    }
+
+    // Version 2: a 0 leaves the bit in the address unchanged, a 1 overwrites it and an X makes it
+    // floating.
+    pub fn decode(&self, address: usize) -> Pattern {
+        Pattern::new((address & self.zeros) | self.ones, self.floating)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply() {
+        // value:  000000000000000000000000000000001011  (decimal 11)
+        // mask:   XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X
+        // result: 000000000000000000000000000001001001  (decimal 73)
+        let mask = Mask::parse("XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X", WIDTH).unwrap();
+
+        assert_eq!(mask.floating, 0b111111111111111111111111111110111101);
+        assert_eq!(mask.ones, 0b000000000000000000000000000001000000);
+        assert_eq!(mask.apply(11), 73);
+        assert_eq!(mask.apply(101), 101);
+        assert_eq!(mask.apply(0), 64);
+    }
+
+    #[test]
+    fn decode() {
+        // address: 000000000000000000000000000000101010  (decimal 42)
+        // mask:    000000000000000000000000000000X1001X
+        // result:  000000000000000000000000000000X1101X
+        let mask = Mask::parse("000000000000000000000000000000X1001X", WIDTH).unwrap();
+        let pattern = mask.decode(0b000000000000000000000000000000101010);
+
+        assert_eq!(
+            pattern,
+            Pattern::new(
+                0b000000000000000000000000000000011010,
+                0b000000000000000000000000000000100001,
+            )
+        );
+        assert_eq!(pattern.len(), 4);
+    }
+
+    #[test]
+    fn other_widths() {
+        let mask = Mask::parse("1X0X", 4).unwrap();
+        assert_eq!(mask.apply(0b0110), 0b1100);
+        // Bits above the width are cleared.
+        assert_eq!(mask.apply(0b110110), 0b1100);
+        assert_eq!(mask.decode(0b0110).len(), 4);
+
+        let mask = Mask::parse(&"X".repeat(64), 64).unwrap();
+        assert_eq!(mask.apply(usize::MAX), usize::MAX);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(Mask::parse("", 0), Err(MaskError::Width(0)));
+        assert_eq!(Mask::parse(&"X".repeat(65), 65), Err(MaskError::Width(65)));
+        assert_eq!(
+            Mask::parse("X1X", 4),
+            Err(MaskError::Length {
+                expected: 4,
+                actual: 3
+            })
+        );
+        assert_eq!(
+            Mask::parse("X1x0", 4).unwrap_err().to_string(),
+            "bit 2 is 'x', not 0, 1 or X"
+        );
+    }
+}
@@ -3,6 +3,8 @@ package main
 import (
 	"fmt"
 	"io/ioutil"
+	"math/big"
+	"math/bits"
 	"strconv"
 	"strings"
 )
@@ -29,7 +31,7 @@ func LoadThen(fileName string, separator string, handler func(s string)) error {
 
 func main() {
 	var input []string
-	err := LoadThen("input", "\n", func(s string) {
+	err := LoadThen("../day_14/input", "\n", func(s string) {
 		for _, rowStr := range strings.Split(s, "\n") {
 			input = append(input, rowStr)
 		}
@@ -42,6 +44,10 @@ func main() {
 	part1 := part1(input) // 9628746976360
 
 	fmt.Printf("Visible (part 1): %v\n", part1)
+
+	part2 := part2(input) // 4574598714592
+
+	fmt.Printf("Sum (part 2): %v\n", part2)
 }
 
 func create_bitmasks(mask string) (int, int) {
@@ -112,3 +118,113 @@ func part1(lines []string) int {
 
 	return sum
 }
+
+// An address pattern: bits set in floating match either 0 or 1, the rest must equal fixed.
+type pattern struct {
+	fixed    int
+	floating int
+}
+
+func new_pattern(fixed, floating int) pattern {
+	return pattern{fixed &^ floating, floating}
+}
+
+// How many addresses the pattern matches. Up to 2^64, so it doesn't fit in an int.
+func (p pattern) len() *big.Int {
+	return new(big.Int).Lsh(big.NewInt(1), uint(bits.OnesCount64(uint64(p.floating))))
+}
+
+// The addresses matched by both patterns, if there are any.
+func (p pattern) intersect(other pattern) (pattern, bool) {
+	both_fixed := ^p.floating & ^other.floating
+	if (p.fixed^other.fixed)&both_fixed != 0 {
+		return pattern{}, false
+	}
+
+	return new_pattern(p.fixed|other.fixed, p.floating&other.floating), true
+}
+
+// The addresses matched by p but not other, as disjoint patterns. Each floating bit that the
+// overlap pins down splits off the half that can't overlap.
+func (p pattern) subtract(other pattern) []pattern {
+	overlap, ok := p.intersect(other)
+	if !ok {
+		return []pattern{p}
+	}
+
+	var pieces []pattern
+	rest := p
+	split := p.floating &^ overlap.floating
+
+	for split != 0 {
+		bit := split & -split
+		split &= split - 1
+
+		floating := rest.floating &^ bit
+		pieces = append(pieces, new_pattern(rest.fixed|(^overlap.fixed&bit), floating))
+		rest = new_pattern(rest.fixed|(overlap.fixed&bit), floating)
+	}
+
+	// What's left of rest is the overlap itself, which is dropped.
+	return pieces
+}
+
+// If the bitmask bit is 0, the corresponding memory address bit is unchanged.
+// If the bitmask bit is 1, the corresponding memory address bit is overwritten with 1.
+// If the bitmask bit is X, the corresponding memory address bit is floating.
+func decode_memory_address(address, zer_mask, one_mask int) pattern {
+	return new_pattern(address|one_mask, zer_mask&^one_mask)
+}
+
+type write struct {
+	pattern pattern
+	value   int
+}
+
+// Each write is kept as the pattern of addresses it covers, rather than expanding every floating
+// bit. Older writes have the new pattern carved out of them, so they stay disjoint. A write can
+// cover so many addresses that the sum overflows an int, so it's summed as a big.Int.
+func part2(lines []string) *big.Int {
+	var zer_mask, one_mask int
+	var writes []write
+
+	for _, line := range lines {
+		// mask line
+		if string(line[1]) == "a" {
+			parts := strings.Split(line, " = ")
+
+			zer_mask, one_mask = create_bitmasks(parts[1])
+		}
+
+		// mem line
+		if string(line[1]) == "e" {
+			line := strings.Split(line, "[")[1]
+			parts := strings.Split(line, "]")
+
+			index, _ := strconv.Atoi(parts[0])
+
+			value_str := strings.Split(parts[1], " = ")[1]
+
+			value, _ := strconv.Atoi(value_str)
+
+			addresses := decode_memory_address(index, zer_mask, one_mask)
+
+			var kept []write
+			for _, old := range writes {
+				for _, piece := range old.pattern.subtract(addresses) {
+					kept = append(kept, write{piece, old.value})
+				}
+			}
+
+			writes = append(kept, write{addresses, value})
+		}
+	}
+
+	sum := new(big.Int)
+
+	for _, w := range writes {
+		sum.Add(sum, new(big.Int).Mul(w.pattern.len(), big.NewInt(int64(w.value))))
+	}
+
+	return sum
+}
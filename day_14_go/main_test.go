@@ -1,6 +1,9 @@
 package main
 
 import (
+	"math/big"
+	"strconv"
+	"strings"
 	"testing"
 )
 
@@ -50,3 +53,63 @@ func Test_part1(t *testing.T) {
 		t.Errorf("part1() = %v, want %v", result, expected_result)
 	}
 }
+
+func Test_part2(t *testing.T) {
+	input := []string{
+		"mask = 000000000000000000000000000000X1001X",
+		"mem[42] = 100",
+		"mask = 00000000000000000000000000000000X0XX",
+		"mem[26] = 1",
+	}
+
+	result := part2(input)
+	expected_result := big.NewInt(208)
+
+	if expected_result.Cmp(result) != 0 {
+		t.Errorf("part2() = %v, want %v", result, expected_result)
+	}
+}
+
+// The fixtures are shared with the Rust version.
+func Test_fixtures(t *testing.T) {
+	dir := "../day_14/fixtures/"
+
+	err := LoadThen(dir+"expected", "\n", func(line string) {
+		if strings.HasPrefix(line, "#") {
+			return
+		}
+
+		fields := strings.Fields(line)
+		if len(fields) != 3 {
+			t.Fatalf("bad fixture line: %v", line)
+		}
+
+		var input []string
+		err := LoadThen(dir+fields[0], "\n", func(s string) {
+			input = append(input, s)
+		})
+		if err != nil {
+			t.Fatal(err)
+		}
+
+		expected_part1, err := strconv.Atoi(fields[1])
+		if err != nil {
+			t.Fatalf("bad part 1 answer for %v: %v", fields[0], err)
+		}
+		expected_part2, ok := new(big.Int).SetString(fields[2], 10)
+		if !ok {
+			t.Fatalf("bad part 2 answer for %v: %v", fields[0], fields[2])
+		}
+
+		if result := part1(input); expected_part1 != result {
+			t.Errorf("part1() of %v = %v, want %v", fields[0], result, expected_part1)
+		}
+		if result := part2(input); expected_part2.Cmp(result) != 0 {
+			t.Errorf("part2() of %v = %v, want %v", fields[0], result, expected_part2)
+		}
+	})
+
+	if err != nil {
+		t.Fatal(err)
+	}
+}
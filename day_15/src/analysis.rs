@@ -0,0 +1,153 @@
+// Statistics over the sequence spoken from a set of starting numbers, for studying how it grows.
+//
+//     cargo run --release --bin day_15 -- csv 2020 0,3,6 > numbers.csv
+
+use crate::memory::Memory;
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    // Turns are numbered from 1.
+    pub first_turn: usize,
+    pub last_turn: usize,
+    pub times_spoken: usize,
+    // The most turns between two times the number was spoken, 0 if it was only spoken once.
+    pub largest_gap: usize,
+}
+
+pub struct Analysis {
+    turns: usize,
+    numbers: HashMap<usize, Stats>,
+}
+
+impl Analysis {
+    pub fn new(starting: &[usize], turns: usize) -> Self {
+        let mut numbers: HashMap<usize, Stats> = HashMap::new();
+
+        for (turn, number) in (1..).zip(Memory::new(starting, turns)) {
+            numbers
+                .entry(number)
+                .and_modify(|stats| {
+                    stats.largest_gap = stats.largest_gap.max(turn - stats.last_turn);
+                    stats.last_turn = turn;
+                    stats.times_spoken += 1;
+                })
+                .or_insert(Stats {
+                    first_turn: turn,
+                    last_turn: turn,
+                    times_spoken: 1,
+                    largest_gap: 0,
+                });
+        }
+
+        Self { turns, numbers }
+    }
+
+    pub fn stats(&self, number: usize) -> Option<Stats> {
+        self.numbers.get(&number).copied()
+    }
+
+    pub fn first_turn(&self, number: usize) -> Option<usize> {
+        self.stats(number).map(|stats| stats.first_turn)
+    }
+
+    // Whether `number` is spoken on any turn before `turn`, or None if that's past the turns
+    // analysed and the number hasn't come up yet.
+    pub fn spoken_before(&self, number: usize, turn: usize) -> Option<bool> {
+        match self.first_turn(number) {
+            Some(first_turn) => Some(first_turn < turn),
+            None if turn <= self.turns + 1 => Some(false),
+            None => None,
+        }
+    }
+
+    // How many numbers were spoken each number of times.
+    pub fn distribution(&self) -> BTreeMap<usize, usize> {
+        let mut distribution = BTreeMap::new();
+        for stats in self.numbers.values() {
+            *distribution.entry(stats.times_spoken).or_insert(0) += 1;
+        }
+        distribution
+    }
+
+    // The number with the largest gap between two of its turns, and the gap. Ties go to the
+    // smallest number.
+    pub fn largest_gap(&self) -> Option<(usize, usize)> {
+        self.numbers
+            .iter()
+            .filter(|(_, stats)| stats.times_spoken > 1)
+            .map(|(number, stats)| (*number, stats.largest_gap))
+            .min_by_key(|(number, gap)| (std::cmp::Reverse(*gap), *number))
+    }
+
+    // One row per number spoken, in numeric order.
+    pub fn to_csv(&self) -> String {
+        let mut numbers: Vec<_> = self.numbers.iter().collect();
+        numbers.sort_unstable_by_key(|(number, _)| **number);
+
+        let mut csv = String::from("number,first_turn,last_turn,times_spoken,largest_gap\n");
+        for (number, stats) in numbers {
+            csv += &format!(
+                "{},{},{},{},{}\n",
+                number, stats.first_turn, stats.last_turn, stats.times_spoken, stats.largest_gap
+            );
+        }
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0, 3, 6, 0, 3, 3, 1, 0, 4, 0
+    fn analysis() -> Analysis {
+        Analysis::new(&[0, 3, 6], 10)
+    }
+
+    #[test]
+    fn stats() {
+        let analysis = analysis();
+
+        assert_eq!(
+            analysis.stats(0),
+            Some(Stats {
+                first_turn: 1,
+                last_turn: 10,
+                times_spoken: 4,
+                largest_gap: 4,
+            })
+        );
+        assert_eq!(analysis.first_turn(4), Some(9));
+        assert_eq!(analysis.first_turn(2), None);
+        assert_eq!(analysis.largest_gap(), Some((0, 4)));
+        assert_eq!(
+            analysis.distribution().into_iter().collect::<Vec<_>>(),
+            vec![(1, 3), (3, 1), (4, 1)]
+        );
+    }
+
+    #[test]
+    fn spoken_before() {
+        let analysis = analysis();
+
+        assert_eq!(analysis.spoken_before(4, 9), Some(false));
+        assert_eq!(analysis.spoken_before(4, 10), Some(true));
+        assert_eq!(analysis.spoken_before(2, 11), Some(false));
+        assert_eq!(analysis.spoken_before(2, 12), None);
+    }
+
+    #[test]
+    fn csv() {
+        assert_eq!(
+            analysis().to_csv(),
+            "number,first_turn,last_turn,times_spoken,largest_gap
+0,1,10,4,4
+1,7,7,1,0
+3,2,6,3,3
+4,9,9,1,0
+6,3,3,1,0
+"
+        );
+    }
+}
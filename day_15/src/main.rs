@@ -69,80 +69,107 @@
 //
 // Given your starting numbers, what will be the 30000000th number spoken?
 
+mod analysis;
+mod memory;
+
+use analysis::Analysis;
+use memory::Memory;
+
+const INPUT: [usize; 6] = [1, 20, 8, 12, 0, 14];
+
 fn main() {
-    let input = vec![1, 20, 8, 12, 0, 14];
+    // cargo run --release --bin day_15 -- [query] <arguments>
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        match run(&args) {
+            Ok(output) => print!("{}", output),
+            Err(error) => {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
 
-    let part_1 = part_1(&input);
+    let part_1 = part_1(&INPUT).unwrap();
     assert_eq!(part_1, 492);
     println!("Part 1: {}", part_1);
 
-    let part_2 = part_2(&input);
+    let part_2 = part_2(&INPUT).unwrap();
     assert_eq!(part_2, 63644);
     println!("Part 2: {}", part_2);
 }
 
-fn part_1(input: &[usize]) -> usize {
+fn part_1(input: &[usize]) -> Option<usize> {
     inner(input, 2020)
 }
 
-fn part_2(input: &[usize]) -> usize {
+fn part_2(input: &[usize]) -> Option<usize> {
     inner(input, 30_000_000)
 }
 
-fn inner(input: &[usize], turns: usize) -> usize {
-    let (mut last_spoken_number, mut memory) = Memory::new(input);
-
-    while memory.turn < turns {
-        last_spoken_number = memory.speak(last_spoken_number);
-    }
-
-    last_spoken_number
-}
-
-use std::collections::HashMap;
-
-#[derive(Default)]
-struct Memory {
-    spoken: HashMap<usize, (Option<usize>, Option<usize>)>,
-    turn: usize,
-}
-
-impl Memory {
-    fn new(input: &[usize]) -> (usize, Self) {
-        let mut s = Self::default();
-
-        let mut last_spoken_number = 0;
-        for &n in input.iter() {
-            s.insert(n);
-            last_spoken_number = n;
+const USAGE: &str = "usage:
+    day_15 <turns> [starting numbers]
+    day_15 csv <turns> [starting numbers]
+    day_15 stats <turns> [starting numbers]
+    day_15 spoken <number> <turn> [starting numbers]";
+
+fn run(args: &[String]) -> Result<String, String> {
+    let number = |arg: &str| -> Result<usize, String> {
+        arg.parse().map_err(|_| format!("not a number: {}", arg))
+    };
+    let starting = |arg: Option<&String>| -> Result<Vec<usize>, String> {
+        match arg {
+            Some(arg) => arg.split(',').map(number).collect(),
+            None => Ok(INPUT.to_vec()),
         }
+    };
+
+    let (query, args) = match args[0].as_str() {
+        "csv" | "stats" | "spoken" => (args[0].as_str(), &args[1..]),
+        _ => ("last", args),
+    };
+    let (n, turns, starting) = match (query, args) {
+        ("spoken", [n, turn, rest @ ..]) if rest.len() <= 1 => {
+            (number(n)?, number(turn)?, starting(rest.first())?)
+        }
+        ("spoken", _) => return Err(USAGE.to_owned()),
+        (_, [turns, rest @ ..]) if rest.len() <= 1 => (0, number(turns)?, starting(rest.first())?),
+        _ => return Err(USAGE.to_owned()),
+    };
 
-        (last_spoken_number, s)
+    if turns == 0 || starting.is_empty() {
+        return Err("need at least one turn and one starting number".to_owned());
     }
 
-    fn insert(&mut self, n: usize) {
-        let entry = self.spoken.entry(n).or_insert((None, None));
-        *entry = (Some(self.turn), entry.0);
-        self.turn += 1;
-    }
+    let output = match query {
+        "csv" => Analysis::new(&starting, turns).to_csv(),
+        "stats" => {
+            let analysis = Analysis::new(&starting, turns);
+            let mut output = String::new();
+            if let Some((number, gap)) = analysis.largest_gap() {
+                output += &format!("Largest gap: {} turns, between {}s\n", gap, number);
+            }
+            for (times, numbers) in analysis.distribution() {
+                output += &format!("Spoken {} times: {} numbers\n", times, numbers);
+            }
+            output
+        }
+        // Only turns before `turns` need analysing.
+        "spoken" => match Analysis::new(&starting, turns - 1).spoken_before(n, turns) {
+            Some(true) => format!("{} is spoken before turn {}\n", n, turns),
+            _ => format!("{} isn't spoken before turn {}\n", n, turns),
+        },
+        // Checked above, so something is spoken.
+        _ => format!("{}\n", inner(&starting, turns).unwrap()),
+    };
+
+    Ok(output)
+}
 
-    // If that was the first time the number has been spoken, the current player says 0.
-    // Otherwise, the number had been spoken before; the current player announces how many turns
-    // apart the number is from when it was previously spoken.
-    fn speak(&mut self, last_spoken_number: usize) -> usize {
-        let number_to_speak = match self
-            .spoken
-            .get(&last_spoken_number)
-            .unwrap_or(&(None, None))
-        {
-            (Some(new), Some(old)) => new - old,
-            _ => 0,
-        };
-
-        self.insert(number_to_speak);
-
-        number_to_speak
-    }
+// The number spoken on the last turn, or None when nothing is spoken at all.
+fn inner(input: &[usize], turns: usize) -> Option<usize> {
+    Memory::new(input, turns).last()
 }
 
 #[cfg(test)]
@@ -151,8 +178,16 @@ mod tests {
 
     #[test]
     fn readme_example() {
-        let input = vec![0, 3, 6];
+        let input = [0, 3, 6];
 
-        assert_eq!(part_1(&input), 436);
+        assert_eq!(part_1(&input), Some(436));
+        assert_eq!(part_1(&[1, 3, 2]), Some(1));
+        assert_eq!(part_1(&[3, 1, 2]), Some(1836));
+    }
+
+    #[test]
+    fn nothing_spoken() {
+        assert_eq!(part_1(&[]), None);
+        assert_eq!(inner(&[0, 3, 6], 0), None);
     }
 }
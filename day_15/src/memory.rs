@@ -0,0 +1,133 @@
+// Remembers the turn each number was last spoken. A number spoken on turn t is either 0 or the gap
+// back to an earlier turn, so after n turns every number is below n (or is a starting number) and
+// a flat Vec indexed by number covers them all. Targets too large for that fall back to a HashMap.
+
+use std::collections::HashMap;
+
+// The most entries a flat store is allowed, 1 GiB of u32s.
+pub const FLAT_LIMIT: usize = 1 << 28;
+
+enum LastSeen {
+    // Indexed by number, 0 for numbers not spoken yet.
+    Flat(Vec<u32>),
+    Sparse(HashMap<usize, usize>),
+}
+
+impl LastSeen {
+    // Records `number` as spoken on `turn`, returning the turn it was spoken before that.
+    fn replace(&mut self, number: usize, turn: usize) -> Option<usize> {
+        match self {
+            LastSeen::Flat(turns) => {
+                let previous = std::mem::replace(&mut turns[number], turn as u32);
+                Some(previous as usize).filter(|turn| *turn > 0)
+            }
+            LastSeen::Sparse(turns) => turns.insert(number, turn),
+        }
+    }
+}
+
+// The numbers spoken on turns 1 to `turns`, starting numbers included.
+pub struct Memory {
+    starting: Vec<usize>,
+    last_seen: LastSeen,
+    // Turns taken so far.
+    turn: usize,
+    turns: usize,
+    last_spoken_number: Option<usize>,
+}
+
+impl Memory {
+    pub fn new(starting: &[usize], turns: usize) -> Self {
+        Self::with_flat_limit(starting, turns, FLAT_LIMIT)
+    }
+
+    // Uses a flat store when it needs at most `flat_limit` entries.
+    pub fn with_flat_limit(starting: &[usize], turns: usize, flat_limit: usize) -> Self {
+        let size = starting
+            .iter()
+            .map(|n| n + 1)
+            .chain(Some(turns))
+            .max()
+            .unwrap();
+
+        let last_seen = if size <= flat_limit && turns <= u32::MAX as usize {
+            LastSeen::Flat(vec![0; size])
+        } else {
+            LastSeen::Sparse(HashMap::new())
+        };
+
+        Self {
+            starting: starting.to_vec(),
+            last_seen,
+            turn: 0,
+            turns,
+            last_spoken_number: None,
+        }
+    }
+
+    // If that was the first time the number has been spoken, the current player says 0.
+    // Otherwise, the number had been spoken before; the current player announces how many turns
+    // apart the number is from when it was previously spoken.
+    fn speak(&mut self, last_spoken_number: usize) -> usize {
+        match self.last_seen.replace(last_spoken_number, self.turn) {
+            Some(previous) => self.turn - previous,
+            None => 0,
+        }
+    }
+}
+
+impl Iterator for Memory {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.turn == self.turns {
+            return None;
+        }
+
+        let number_to_speak = match self.last_spoken_number {
+            Some(last_spoken_number) => {
+                let number = self.speak(last_spoken_number);
+                self.starting.get(self.turn).copied().unwrap_or(number)
+            }
+            // With no starting numbers there's nothing to say.
+            None => self.starting.first().copied()?,
+        };
+
+        self.turn += 1;
+        self.last_spoken_number = Some(number_to_speak);
+
+        Some(number_to_speak)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sequence() {
+        assert_eq!(
+            Memory::new(&[0, 3, 6], 10).collect::<Vec<_>>(),
+            vec![0, 3, 6, 0, 3, 3, 1, 0, 4, 0]
+        );
+        assert_eq!(Memory::new(&[0, 3, 6], 2).collect::<Vec<_>>(), vec![0, 3]);
+        assert_eq!(Memory::new(&[0, 3, 6], 0).count(), 0);
+        assert_eq!(Memory::new(&[], 10).count(), 0);
+
+        // Repeated starting numbers still count as spoken.
+        assert_eq!(
+            Memory::new(&[1, 1], 5).collect::<Vec<_>>(),
+            vec![1, 1, 1, 1, 1]
+        );
+    }
+
+    #[test]
+    fn sparse_matches_flat() {
+        for starting in &[vec![0, 3, 6], vec![3, 1, 2], vec![1_000_000, 0]] {
+            let flat: Vec<_> = Memory::new(starting, 5000).collect();
+            let sparse: Vec<_> = Memory::with_flat_limit(starting, 5000, 0).collect();
+
+            assert_eq!(flat, sparse);
+        }
+    }
+}
@@ -0,0 +1,88 @@
+// Sets of values stored as sorted, disjoint ranges. Overlapping and touching ranges are merged
+// when the set is built, so membership is a binary search for the first range that doesn't end
+// before the value.
+
+use std::ops::RangeInclusive;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Intervals {
+    ranges: Vec<RangeInclusive<usize>>,
+}
+
+impl Intervals {
+    pub fn new<I>(ranges: I) -> Self
+    where
+        I: IntoIterator<Item = RangeInclusive<usize>>,
+    {
+        let mut sorted: Vec<_> = ranges.into_iter().filter(|r| !r.is_empty()).collect();
+        sorted.sort_unstable_by_key(|r| *r.start());
+
+        let mut ranges: Vec<RangeInclusive<usize>> = Vec::with_capacity(sorted.len());
+        for range in sorted {
+            match ranges.last_mut() {
+                Some(last) if *range.start() <= last.end().saturating_add(1) => {
+                    if range.end() > last.end() {
+                        *last = *last.start()..=*range.end();
+                    }
+                }
+                _ => ranges.push(range),
+            }
+        }
+
+        Self { ranges }
+    }
+
+    // Every value in any of the sets.
+    pub fn union<'a, I>(sets: I) -> Self
+    where
+        I: IntoIterator<Item = &'a Intervals>,
+    {
+        Self::new(sets.into_iter().flat_map(|set| set.ranges.iter().cloned()))
+    }
+
+    pub fn ranges(&self) -> &[RangeInclusive<usize>] {
+        &self.ranges
+    }
+
+    pub fn contains(&self, value: usize) -> bool {
+        let i = self.ranges.partition_point(|r| *r.end() < value);
+        self.ranges.get(i).is_some_and(|r| *r.start() <= value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_ranges() {
+        let set = Intervals::new([33..=44, 1..=3, 5..=7, 6..=11, 12..=12, 40..=41]);
+
+        assert_eq!(set.ranges, vec![1..=3, 5..=12, 33..=44]);
+        assert!(!set.contains(0));
+        assert!(set.contains(1));
+        assert!(!set.contains(4));
+        assert!(set.contains(12));
+        assert!(!set.contains(13));
+        assert!(set.contains(44));
+        assert!(!set.contains(45));
+
+        assert_eq!(
+            Intervals::new([5..=usize::MAX, 0..=4]).ranges,
+            vec![0..=usize::MAX]
+        );
+        assert!(!Intervals::default().contains(0));
+    }
+
+    #[test]
+    fn union() {
+        let a = Intervals::new([1..=3, 5..=7]);
+        let b = Intervals::new([13..=40, 45..=50]);
+        let c = Intervals::new([6..=11, 33..=44]);
+
+        assert_eq!(
+            Intervals::union(&[a, b, c]).ranges,
+            vec![1..=3, 5..=11, 13..=50]
+        );
+    }
+}
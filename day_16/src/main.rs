@@ -1,4 +1,12 @@
+mod intervals;
+mod report;
+mod resolve;
+
+use intervals::Intervals;
+use report::Report;
+use resolve::{BitSet, Resolution};
 use std::collections::HashMap;
+use std::fmt;
 use std::ops::RangeInclusive;
 
 type Ticket = Vec<usize>;
@@ -6,119 +14,182 @@ type Ticket = Vec<usize>;
 #[derive(Clone, Debug, PartialEq)]
 struct Rule {
     name: String,
-    range_1: RangeInclusive<usize>,
-    range_2: RangeInclusive<usize>,
+    ranges: Intervals,
 }
 
 impl Rule {
-    fn new(name: String, range_1: RangeInclusive<usize>, range_2: RangeInclusive<usize>) -> Self {
+    fn new<I>(name: String, ranges: I) -> Self
+    where
+        I: IntoIterator<Item = RangeInclusive<usize>>,
+    {
         Self {
             name,
-            range_1,
-            range_2,
+            ranges: Intervals::new(ranges),
         }
     }
 
-    fn valid(&self, value: &usize) -> bool {
-        self.range_1.contains(value) || self.range_2.contains(value)
+    fn valid(&self, value: usize) -> bool {
+        self.ranges.contains(value)
     }
 }
 
+// Every value valid for at least one rule.
+fn valid_for_any(rules: &[Rule]) -> Intervals {
+    Intervals::union(rules.iter().map(|rule| &rule.ranges))
+}
+
 fn main() {
     let input = include_str!("../input");
 
-    let part_1 = part_1(&input);
+    // cargo run --bin day_16 -- <json|table> [input file]
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        match report(input, &args) {
+            Ok(output) => print!("{}", output),
+            Err(error) => {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let part_1 = part_1(input);
     assert_eq!(part_1, 28_884);
     println!("Part 1: {}", part_1);
 
-    let part_2 = part_2(&input);
+    let part_2 = part_2(input);
     assert_eq!(part_2, 1_001_849_322_119);
     println!("Part 2: {}", part_2);
 }
 
+fn report(input: &str, args: &[String]) -> Result<String, String> {
+    let (format, input) = match args {
+        [format] => (format, input.to_owned()),
+        [format, path] => (
+            format,
+            std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?,
+        ),
+        _ => return Err("usage: day_16 <json|table> [input file]".to_owned()),
+    };
+
+    let (rules, my_ticket, nearby_tickets) = parse(&input);
+    let report = Report::new(&rules, my_ticket.len(), &nearby_tickets);
+
+    match format.as_str() {
+        "json" => Ok(report.to_json()),
+        "table" => Ok(report.to_table()),
+        _ => Err(format!(
+            "unknown format {:?}, expected json or table",
+            format
+        )),
+    }
+}
+
 fn part_1(input: &str) -> usize {
-    let (rules, _my_ticket, nearby_tickets) = parse(&input);
+    let (rules, _my_ticket, nearby_tickets) = parse(input);
+    let valid = valid_for_any(&rules);
 
     nearby_tickets
         .iter()
         .flatten()
-        .filter(|value| !rules.iter().any(|rule| rule.valid(value)))
+        .filter(|value| !valid.contains(**value))
         .sum()
 }
 
 fn part_2(input: &str) -> usize {
-    let (rules, my_ticket, nearby_tickets) = parse(&input);
+    let (rules, my_ticket, nearby_tickets) = parse(input);
+    let valid = valid_for_any(&rules);
 
     let valid_tickets: Vec<_> = nearby_tickets
         .into_iter()
-        .filter(|ticket| ticket_valid(&rules, ticket))
+        .filter(|ticket| ticket_valid(&valid, ticket))
         .collect();
 
-    determine_indexes(&rules, valid_tickets)
+    determine_indexes(&rules, my_ticket.len(), &valid_tickets)
+        .unwrap_or_else(|error| panic!("{}", error))
         .iter()
         .filter(|(name, _)| name.starts_with("departure"))
         .map(|(_, idx)| my_ticket[*idx])
         .product()
 }
 
-fn determine_indexes(rules: &[Rule], tickets: Vec<Ticket>) -> HashMap<String, usize> {
-    let rules = rules.to_vec();
-    let mut indexes_to_rules = HashMap::new();
-
-    for (i, column) in transpose(tickets).iter().enumerate() {
-        let x: Vec<_> = rules
-            .iter()
-            .filter(|rule| column.iter().all(|value| rule.valid(value)))
-            .map(|rule| rule.name.clone())
-            .collect();
-        indexes_to_rules.insert(i, x);
-    }
+#[derive(Debug, PartialEq)]
+enum ResolveError {
+    // Columns that could be more than one field, with every field each could be.
+    Ambiguous(Vec<(usize, Vec<String>)>),
+    // Columns left over once every field that can be placed has been.
+    Impossible(Vec<usize>),
+}
 
-    let mut ret = HashMap::new();
-    loop {
-        let x: Option<(usize, String)> = {
-            (&indexes_to_rules
-                .iter()
-                .find(|(_k, v)| v.len() == 1)
-                .map(|(k, v)| (*k, v.first().unwrap().clone())))
-                .clone()
-        };
-
-        if let Some((idx, only_one_valid_rule_name)) = x {
-            for rules in indexes_to_rules.values_mut() {
-                if rules.len() > 1 {
-                    let mut x = rules.to_vec();
-                    x.retain(|n| n != &only_one_valid_rule_name);
-                    *rules = x;
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ResolveError::Ambiguous(columns) => {
+                write!(f, "more than one field fits")?;
+                for (column, names) in columns {
+                    write!(f, "\n  column {}: {}", column, names.join(", "))?;
                 }
+                Ok(())
+            }
+            ResolveError::Impossible(columns) => {
+                let columns: Vec<_> = columns.iter().map(|c| c.to_string()).collect();
+                write!(f, "no field left for columns {}", columns.join(", "))
             }
-            indexes_to_rules.remove(&idx);
-
-            ret.insert(only_one_valid_rule_name.clone(), idx);
-            1;
-        } else {
-            break;
         }
     }
+}
 
-    ret
+fn determine_indexes(
+    rules: &[Rule],
+    columns: usize,
+    tickets: &[Ticket],
+) -> Result<HashMap<String, usize>, ResolveError> {
+    match resolve::resolve(&candidates(rules, columns, tickets), rules.len()) {
+        Resolution::Unique(assignment) => Ok(assignment
+            .into_iter()
+            .enumerate()
+            .map(|(column, rule)| (rules[rule].name.clone(), column))
+            .collect()),
+        Resolution::Ambiguous {
+            assignment,
+            alternatives,
+        } => Err(ResolveError::Ambiguous(
+            alternatives
+                .into_iter()
+                .map(|(column, others)| {
+                    let names = Some(assignment[column])
+                        .into_iter()
+                        .chain(others)
+                        .map(|rule| rules[rule].name.clone())
+                        .collect();
+                    (column, names)
+                })
+                .collect(),
+        )),
+        Resolution::Impossible { unmatched, .. } => Err(ResolveError::Impossible(unmatched)),
+    }
 }
 
-// https://stackoverflow.com/a/64499219/826820
-fn transpose<T>(v: Vec<Vec<T>>) -> Vec<Vec<T>>
-where
-    T: Clone,
-{
-    assert!(!v.is_empty());
-    (0..v[0].len())
-        .map(|i| v.iter().map(|inner| inner[i].clone()).collect::<Vec<T>>())
+// The rules every ticket satisfies in each of the `columns` columns. The count comes from your
+// ticket rather than the nearby ones, so with no valid nearby tickets every rule fits every column
+// instead of there being no columns to assign.
+fn candidates(rules: &[Rule], columns: usize, tickets: &[Ticket]) -> Vec<BitSet> {
+    (0..columns)
+        .map(|column| {
+            let mut set = BitSet::new(rules.len());
+            for (i, rule) in rules.iter().enumerate() {
+                if tickets.iter().all(|ticket| rule.valid(ticket[column])) {
+                    set.insert(i);
+                }
+            }
+            set
+        })
         .collect()
 }
 
-fn ticket_valid(rules: &[Rule], ticket: &Ticket) -> bool {
-    ticket
-        .iter()
-        .all(|value| rules.iter().any(|rule| rule.valid(value)))
+fn ticket_valid(valid: &Intervals, ticket: &[usize]) -> bool {
+    ticket.iter().all(|value| valid.contains(*value))
 }
 
 fn parse(input: &str) -> (Vec<Rule>, Ticket, Vec<Ticket>) {
@@ -146,10 +217,14 @@ fn parse(input: &str) -> (Vec<Rule>, Ticket, Vec<Ticket>) {
 }
 
 fn parse_rules(input: &[String]) -> Vec<Rule> {
-    input.iter().map(parse_rule_line).collect()
+    input
+        .iter()
+        .map(String::as_str)
+        .map(parse_rule_line)
+        .collect()
 }
 
-fn parse_rule_line(line: &String) -> Rule {
+fn parse_rule_line(line: &str) -> Rule {
     let to_range = |r: &str| {
         let mut iter = r.split('-');
         let min: usize = iter.next().unwrap().parse().unwrap();
@@ -160,13 +235,9 @@ fn parse_rule_line(line: &String) -> Rule {
 
     let mut parts = line.split(": ");
     let name = parts.next().unwrap().to_string();
-    let mut parts = parts.next().unwrap().split(" or ");
+    let ranges = parts.next().unwrap().split(" or ").map(to_range);
 
-    Rule::new(
-        name,
-        to_range(parts.next().unwrap()),
-        to_range(parts.next().unwrap()),
-    )
+    Rule::new(name, ranges)
 }
 
 fn parse_ticket(ticket: &str) -> Ticket {
@@ -177,7 +248,7 @@ fn parse_ticket(ticket: &str) -> Ticket {
 mod tests {
     use super::*;
 
-    const INPUT: &'static str = "class: 1-3 or 5-7
+    const INPUT: &str = "class: 1-3 or 5-7
 row space: 6-11 or 33-44
 seat: 13-40 or 45-50
 
@@ -192,7 +263,7 @@ nearby tickets:
 
     #[test]
     fn readme_example() {
-        assert_eq!(part_1(&INPUT), 71);
+        assert_eq!(part_1(INPUT), 71);
     }
 
     #[test]
@@ -208,26 +279,56 @@ nearby tickets:
 3,9,18
 15,1,5
 5,14,9";
-        let (rules, _my_ticket, nearby_tickets) = parse(&input);
+        let (rules, _my_ticket, nearby_tickets) = parse(input);
 
         let mut expected = HashMap::new();
         expected.insert("class".to_string(), 1);
         expected.insert("row".to_string(), 0);
         expected.insert("seat".to_string(), 2);
 
-        assert_eq!(determine_indexes(&rules, nearby_tickets), expected);
+        assert_eq!(determine_indexes(&rules, 3, &nearby_tickets), Ok(expected));
+    }
+
+    #[test]
+    fn determine_indexes_errors() {
+        let rules = [
+            Rule::new("class".to_string(), [1..=3, 5..=7]),
+            Rule::new("row".to_string(), [1..=3, 5..=7]),
+            Rule::new("seat".to_string(), [13..=40, 45..=50]),
+        ];
+
+        assert_eq!(
+            determine_indexes(&rules, 3, &[vec![1, 2, 13]])
+                .unwrap_err()
+                .to_string(),
+            "more than one field fits\n  column 0: class, row\n  column 1: row, class"
+        );
+        assert_eq!(
+            determine_indexes(&rules, 3, &[vec![1, 20, 13]]),
+            Err(ResolveError::Impossible(vec![2]))
+        );
+
+        // With no tickets to rule anything out, every field fits every column.
+        assert!(matches!(
+            determine_indexes(&rules, 3, &[]),
+            Err(ResolveError::Ambiguous(_))
+        ));
+        assert_eq!(
+            determine_indexes(&rules[2..], 3, &[]),
+            Err(ResolveError::Impossible(vec![1, 2]))
+        );
     }
 
     #[test]
     fn test_parse() {
-        let (rules, my_ticket, nearby_tickets) = parse(&INPUT);
+        let (rules, my_ticket, nearby_tickets) = parse(INPUT);
 
         assert_eq!(
             rules,
             vec![
-                Rule::new("class".to_string(), 1..=3, 5..=7),
-                Rule::new("row space".to_string(), 6..=11, 33..=44),
-                Rule::new("seat".to_string(), 13..=40, 45..=50)
+                Rule::new("class".to_string(), [1..=3, 5..=7]),
+                Rule::new("row space".to_string(), [6..=11, 33..=44]),
+                Rule::new("seat".to_string(), [13..=40, 45..=50])
             ]
         );
 
@@ -246,10 +347,12 @@ nearby tickets:
 
     #[test]
     fn test_parse_rules() {
-        let input: Vec<_> = vec![
+        let input: Vec<_> = [
             "class: 1-3 or 5-7",
             "row: 6-11 or 33-44",
             "seat: 13-40 or 45-50",
+            "zone: 1-2 or 8-9 or 3-5 or 20-20",
+            "wagon: 4-8",
         ]
         .iter()
         .map(|s| s.to_string())
@@ -258,24 +361,28 @@ nearby tickets:
         assert_eq!(
             parse_rules(&input),
             vec![
-                Rule::new("class".to_string(), 1..=3, 5..=7),
-                Rule::new("row".to_string(), 6..=11, 33..=44),
-                Rule::new("seat".to_string(), 13..=40, 45..=50)
+                Rule::new("class".to_string(), [1..=3, 5..=7]),
+                Rule::new("row".to_string(), [6..=11, 33..=44]),
+                Rule::new("seat".to_string(), [13..=40, 45..=50]),
+                Rule::new("zone".to_string(), [1..=5, 8..=9, 20..=20]),
+                Rule::new("wagon".to_string(), [4..=8]),
             ]
         );
     }
 
     #[test]
     fn test_ticket_valid() {
-        let rules = vec![
-            Rule::new("class".to_string(), 1..=3, 5..=7),
-            Rule::new("row".to_string(), 6..=11, 33..=44),
-            Rule::new("seat".to_string(), 13..=40, 45..=50),
+        let rules = [
+            Rule::new("class".to_string(), [1..=3, 5..=7]),
+            Rule::new("row".to_string(), [6..=11, 33..=44]),
+            Rule::new("seat".to_string(), [13..=40, 45..=50]),
         ];
 
-        assert_eq!(ticket_valid(&rules, &vec![7, 3, 47]), true);
-        assert_eq!(ticket_valid(&rules, &vec![40, 4, 50]), false);
-        assert_eq!(ticket_valid(&rules, &vec![55, 2, 20]), false);
-        assert_eq!(ticket_valid(&rules, &vec![38, 6, 12]), false);
+        let rules = valid_for_any(&rules);
+
+        assert!(ticket_valid(&rules, &[7, 3, 47]));
+        assert!(!ticket_valid(&rules, &[40, 4, 50]));
+        assert!(!ticket_valid(&rules, &[55, 2, 20]));
+        assert!(!ticket_valid(&rules, &[38, 6, 12]));
     }
 }
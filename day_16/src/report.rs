@@ -0,0 +1,358 @@
+// A scanning report over the nearby tickets: every value no rule allows, with the range it came
+// closest to, and the fields each column could be before and after resolving the assignment.
+//
+//     cargo run --bin day_16 -- <json|table> [input file]
+
+use crate::resolve::{self, Resolution};
+use crate::{candidates, ticket_valid, valid_for_any, Rule, Ticket};
+use std::ops::RangeInclusive;
+
+#[derive(Debug, PartialEq)]
+pub struct Miss {
+    pub rule: String,
+    pub range: RangeInclusive<usize>,
+    pub distance: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct InvalidValue {
+    // Indexes into the nearby tickets and their values.
+    pub ticket: usize,
+    pub column: usize,
+    pub value: usize,
+    // None when there are no rules at all.
+    pub closest: Option<Miss>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Column {
+    pub column: usize,
+    // Fields every valid ticket allows in this column.
+    pub before: Vec<String>,
+    // Fields the column is in some complete assignment, or the field a partial assignment gave it
+    // when there's no complete one.
+    pub after: Vec<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Report {
+    pub invalid: Vec<InvalidValue>,
+    pub columns: Vec<Column>,
+}
+
+impl Report {
+    pub fn new(rules: &[Rule], columns: usize, nearby_tickets: &[Ticket]) -> Self {
+        let valid = valid_for_any(rules);
+
+        let invalid = nearby_tickets
+            .iter()
+            .enumerate()
+            .flat_map(|(ticket, values)| {
+                values
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, value)| !valid.contains(**value))
+                    .map(move |(column, value)| InvalidValue {
+                        ticket,
+                        column,
+                        value: *value,
+                        closest: closest_miss(rules, *value),
+                    })
+            })
+            .collect();
+
+        let valid_tickets: Vec<_> = nearby_tickets
+            .iter()
+            .filter(|ticket| ticket_valid(&valid, ticket))
+            .cloned()
+            .collect();
+        let candidates = candidates(rules, columns, &valid_tickets);
+
+        let mut after: Vec<Vec<usize>> = match resolve::resolve(&candidates, rules.len()) {
+            Resolution::Unique(assignment) => assignment.into_iter().map(|r| vec![r]).collect(),
+            Resolution::Ambiguous {
+                assignment,
+                alternatives,
+            } => {
+                let mut after: Vec<_> = assignment.into_iter().map(|r| vec![r]).collect();
+                for (column, rules) in alternatives {
+                    after[column].extend(rules);
+                }
+                after
+            }
+            Resolution::Impossible { assignment, .. } => assignment
+                .into_iter()
+                .map(|r| r.into_iter().collect())
+                .collect(),
+        };
+
+        let names = |indexes: &mut dyn Iterator<Item = usize>| -> Vec<String> {
+            indexes.map(|i| rules[i].name.clone()).collect()
+        };
+
+        let columns = candidates
+            .iter()
+            .zip(after.iter_mut())
+            .enumerate()
+            .map(|(column, (before, after))| {
+                after.sort_unstable();
+                Column {
+                    column,
+                    before: names(&mut before.iter()),
+                    after: names(&mut after.iter().copied()),
+                }
+            })
+            .collect();
+
+        Self { invalid, columns }
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{\n  \"invalid\": [");
+
+        for (i, invalid) in self.invalid.iter().enumerate() {
+            let closest = match &invalid.closest {
+                Some(miss) => format!(
+                    "{{\"rule\": {}, \"start\": {}, \"end\": {}, \"distance\": {}}}",
+                    json_string(&miss.rule),
+                    miss.range.start(),
+                    miss.range.end(),
+                    miss.distance
+                ),
+                None => "null".to_owned(),
+            };
+
+            json += if i == 0 { "\n" } else { ",\n" };
+            json += &format!(
+                "    {{\"ticket\": {}, \"column\": {}, \"value\": {}, \"closest\": {}}}",
+                invalid.ticket, invalid.column, invalid.value, closest
+            );
+        }
+        json += if self.invalid.is_empty() {
+            "],\n"
+        } else {
+            "\n  ],\n"
+        };
+
+        json += "  \"columns\": [";
+        for (i, column) in self.columns.iter().enumerate() {
+            json += if i == 0 { "\n" } else { ",\n" };
+            json += &format!(
+                "    {{\"column\": {}, \"before\": {}, \"after\": {}}}",
+                column.column,
+                json_strings(&column.before),
+                json_strings(&column.after)
+            );
+        }
+        json += if self.columns.is_empty() {
+            "]\n}\n"
+        } else {
+            "\n  ]\n}\n"
+        };
+
+        json
+    }
+
+    pub fn to_table(&self) -> String {
+        let invalid: Vec<_> = self
+            .invalid
+            .iter()
+            .map(|invalid| {
+                let (rule, range, distance) = match &invalid.closest {
+                    Some(miss) => (
+                        miss.rule.clone(),
+                        format!("{}-{}", miss.range.start(), miss.range.end()),
+                        miss.distance.to_string(),
+                    ),
+                    None => ("-".to_owned(), "-".to_owned(), "-".to_owned()),
+                };
+                vec![
+                    invalid.ticket.to_string(),
+                    invalid.column.to_string(),
+                    invalid.value.to_string(),
+                    rule,
+                    range,
+                    distance,
+                ]
+            })
+            .collect();
+
+        let columns: Vec<_> = self
+            .columns
+            .iter()
+            .map(|column| {
+                vec![
+                    column.column.to_string(),
+                    column.before.join(", "),
+                    column.after.join(", "),
+                ]
+            })
+            .collect();
+
+        format!(
+            "{}\n{}",
+            table(
+                &[
+                    "ticket",
+                    "column",
+                    "value",
+                    "closest rule",
+                    "range",
+                    "distance"
+                ],
+                &invalid
+            ),
+            table(&["column", "before", "after"], &columns)
+        )
+    }
+}
+
+// The rule range nearest to `value`. Ties go to the earlier rule, then the earlier range.
+fn closest_miss(rules: &[Rule], value: usize) -> Option<Miss> {
+    rules
+        .iter()
+        .flat_map(|rule| rule.ranges.ranges().iter().map(move |range| (rule, range)))
+        .map(|(rule, range)| {
+            let distance = if value < *range.start() {
+                range.start() - value
+            } else {
+                value.saturating_sub(*range.end())
+            };
+            (distance, rule, range)
+        })
+        .min_by_key(|(distance, _, _)| *distance)
+        .map(|(distance, rule, range)| Miss {
+            rule: rule.name.clone(),
+            range: range.clone(),
+            distance,
+        })
+}
+
+fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<_> = headers.iter().map(|h| h.len()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let line = |cells: &mut dyn Iterator<Item = &str>| -> String {
+        let padded: Vec<_> = cells
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect();
+        padded.join("  ").trim_end().to_owned() + "\n"
+    };
+
+    let mut table = line(&mut headers.iter().copied());
+    for row in rows {
+        table += &line(&mut row.iter().map(String::as_str));
+    }
+    table
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => json += "\\\"",
+            '\\' => json += "\\\\",
+            c if c.is_control() => json += &format!("\\u{:04x}", c as u32),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn json_strings(strings: &[String]) -> String {
+    let strings: Vec<_> = strings.iter().map(|s| json_string(s)).collect();
+    format!("[{}]", strings.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+
+    const INPUT: &str = "class: 0-1 or 4-19
+row: 0-5 or 8-19
+seat: 0-13 or 16-19
+
+your ticket:
+11,12,13
+
+nearby tickets:
+3,9,18
+15,1,5
+5,14,9
+5,20,7";
+
+    fn report() -> Report {
+        let (rules, my_ticket, nearby_tickets) = parse(INPUT);
+        Report::new(&rules, my_ticket.len(), &nearby_tickets)
+    }
+
+    #[test]
+    fn invalid_values() {
+        assert_eq!(
+            report().invalid,
+            vec![InvalidValue {
+                ticket: 3,
+                column: 1,
+                value: 20,
+                closest: Some(Miss {
+                    rule: "class".to_owned(),
+                    range: 4..=19,
+                    distance: 1
+                }),
+            }]
+        );
+    }
+
+    #[test]
+    fn columns() {
+        let columns = report().columns;
+
+        assert_eq!(columns[0].before, vec!["row"]);
+        assert_eq!(columns[0].after, vec!["row"]);
+        assert_eq!(columns[1].before, vec!["class", "row"]);
+        assert_eq!(columns[1].after, vec!["class"]);
+        assert_eq!(columns[2].before, vec!["class", "row", "seat"]);
+        assert_eq!(columns[2].after, vec!["seat"]);
+    }
+
+    #[test]
+    fn json() {
+        assert_eq!(
+            report().to_json(),
+            r#"{
+  "invalid": [
+    {"ticket": 3, "column": 1, "value": 20, "closest": {"rule": "class", "start": 4, "end": 19, "distance": 1}}
+  ],
+  "columns": [
+    {"column": 0, "before": ["row"], "after": ["row"]},
+    {"column": 1, "before": ["class", "row"], "after": ["class"]},
+    {"column": 2, "before": ["class", "row", "seat"], "after": ["seat"]}
+  ]
+}
+"#
+        );
+        assert_eq!(json_string("a \"b\"\n"), r#""a \"b\"\u000a""#);
+    }
+
+    #[test]
+    fn table() {
+        assert_eq!(
+            report().to_table(),
+            "ticket  column  value  closest rule  range  distance
+3       1       20     class         4-19   1
+
+column  before            after
+0       row               row
+1       class, row        class
+2       class, row, seat  seat
+"
+        );
+    }
+}
@@ -0,0 +1,309 @@
+// Works out which rule belongs to which column. Each column has a set of candidate rules (the ones
+// every valid ticket satisfies in that column), and an assignment is a matching in that bipartite
+// graph that gives every column its own rule. Hopcroft-Karp finds a maximum matching, and then
+// alternating paths show whether any column could swap to a different rule.
+
+use std::collections::VecDeque;
+
+// Rule indexes, one bit each.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    pub fn new(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(64)],
+        }
+    }
+
+    pub fn insert(&mut self, i: usize) {
+        self.words[i / 64] |= 1 << (i % 64);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, word)| {
+            (0..64)
+                .filter(move |bit| word & (1 << bit) != 0)
+                .map(move |bit| i * 64 + bit)
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Resolution {
+    // The rule for each column, and no other assignment works.
+    Unique(Vec<usize>),
+    // One assignment that works, and every other rule each column could take in some other
+    // assignment.
+    Ambiguous {
+        assignment: Vec<usize>,
+        alternatives: Vec<(usize, Vec<usize>)>,
+    },
+    // No assignment covers every column. The rules given to each column by a maximum matching,
+    // and the columns it left without one.
+    Impossible {
+        assignment: Vec<Option<usize>>,
+        unmatched: Vec<usize>,
+    },
+}
+
+pub fn resolve(candidates: &[BitSet], rules: usize) -> Resolution {
+    let mut matching = Matching::new(candidates, rules);
+    matching.maximise();
+
+    let unmatched: Vec<_> = (0..candidates.len())
+        .filter(|column| matching.rule_of[*column].is_none())
+        .collect();
+    if !unmatched.is_empty() {
+        return Resolution::Impossible {
+            assignment: matching.rule_of,
+            unmatched,
+        };
+    }
+
+    let assignment: Vec<_> = matching.rule_of.iter().map(|r| r.unwrap()).collect();
+    let alternatives = matching.alternatives();
+
+    if alternatives.is_empty() {
+        Resolution::Unique(assignment)
+    } else {
+        Resolution::Ambiguous {
+            assignment,
+            alternatives,
+        }
+    }
+}
+
+struct Matching<'a> {
+    candidates: &'a [BitSet],
+    rule_of: Vec<Option<usize>>,
+    column_of: Vec<Option<usize>>,
+    // BFS layer of each column, None once it's known not to lead anywhere.
+    layer: Vec<Option<usize>>,
+}
+
+impl<'a> Matching<'a> {
+    fn new(candidates: &'a [BitSet], rules: usize) -> Self {
+        Self {
+            candidates,
+            rule_of: vec![None; candidates.len()],
+            column_of: vec![None; rules],
+            layer: vec![None; candidates.len()],
+        }
+    }
+
+    fn maximise(&mut self) {
+        while self.layer_columns() {
+            for column in 0..self.candidates.len() {
+                if self.rule_of[column].is_none() {
+                    self.augment(column);
+                }
+            }
+        }
+    }
+
+    // Layers the columns by alternating path length from the unmatched ones, returning whether an
+    // augmenting path exists.
+    fn layer_columns(&mut self) -> bool {
+        let mut queue = VecDeque::new();
+        for column in 0..self.candidates.len() {
+            self.layer[column] = match self.rule_of[column] {
+                Some(_) => None,
+                None => {
+                    queue.push_back(column);
+                    Some(0)
+                }
+            };
+        }
+
+        let mut found = false;
+        while let Some(column) = queue.pop_front() {
+            let next = self.layer[column].map(|layer| layer + 1);
+            for rule in self.candidates[column].iter() {
+                match self.column_of[rule] {
+                    None => found = true,
+                    Some(other) if self.layer[other].is_none() => {
+                        self.layer[other] = next;
+                        queue.push_back(other);
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+
+        found
+    }
+
+    fn augment(&mut self, column: usize) -> bool {
+        let next = self.layer[column].map(|layer| layer + 1);
+
+        for rule in self.candidates[column].iter() {
+            let extends = match self.column_of[rule] {
+                None => true,
+                Some(other) => self.layer[other] == next && self.augment(other),
+            };
+
+            if extends {
+                self.rule_of[column] = Some(rule);
+                self.column_of[rule] = Some(column);
+                return true;
+            }
+        }
+
+        self.layer[column] = None;
+        false
+    }
+
+    // With every column matched, a column can take another candidate rule if that rule is free,
+    // or if the column holding it can in turn move along an alternating path that ends either back
+    // at this column or at a free rule.
+    fn alternatives(&self) -> Vec<(usize, Vec<usize>)> {
+        let columns = self.candidates.len();
+
+        // Column -> columns whose rule it could take.
+        let edges: Vec<Vec<usize>> = (0..columns)
+            .map(|column| {
+                self.other_rules(column)
+                    .filter_map(|rule| self.column_of[rule])
+                    .collect()
+            })
+            .collect();
+        let reachable: Vec<Vec<bool>> = (0..columns).map(|c| Self::reach(&edges, c)).collect();
+
+        let frees: Vec<bool> = (0..columns)
+            .map(|column| {
+                self.other_rules(column)
+                    .any(|rule| self.column_of[rule].is_none())
+            })
+            .collect();
+        let escapes: Vec<bool> = reachable
+            .iter()
+            .map(|reach| (0..columns).any(|c| reach[c] && frees[c]))
+            .collect();
+
+        (0..columns)
+            .filter_map(|column| {
+                let rules: Vec<_> = self
+                    .other_rules(column)
+                    .filter(|rule| match self.column_of[*rule] {
+                        None => true,
+                        Some(other) => reachable[other][column] || escapes[other],
+                    })
+                    .collect();
+
+                if rules.is_empty() {
+                    None
+                } else {
+                    Some((column, rules))
+                }
+            })
+            .collect()
+    }
+
+    fn other_rules(&self, column: usize) -> impl Iterator<Item = usize> + '_ {
+        let matched = self.rule_of[column];
+        self.candidates[column]
+            .iter()
+            .filter(move |rule| Some(*rule) != matched)
+    }
+
+    // Every column reachable from `start`, including itself.
+    fn reach(edges: &[Vec<usize>], start: usize) -> Vec<bool> {
+        let mut seen = vec![false; edges.len()];
+        let mut stack = vec![start];
+        seen[start] = true;
+
+        while let Some(column) = stack.pop() {
+            for next in &edges[column] {
+                if !seen[*next] {
+                    seen[*next] = true;
+                    stack.push(*next);
+                }
+            }
+        }
+
+        seen
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sets(rules: usize, columns: &[&[usize]]) -> Vec<BitSet> {
+        columns
+            .iter()
+            .map(|column| {
+                let mut set = BitSet::new(rules);
+                for rule in *column {
+                    set.insert(*rule);
+                }
+                set
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bit_set() {
+        let mut set = BitSet::new(130);
+        set.insert(0);
+        set.insert(64);
+        set.insert(129);
+
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![0, 64, 129]);
+    }
+
+    #[test]
+    fn unique() {
+        // The puzzle's example: row, class, seat.
+        let candidates = sets(3, &[&[1][..], &[0, 1], &[0, 1, 2]]);
+
+        assert_eq!(resolve(&candidates, 3), Resolution::Unique(vec![1, 0, 2]));
+    }
+
+    #[test]
+    fn reassigns_earlier_columns() {
+        // Column 0 takes rule 0 first and has to give it up for column 1.
+        let candidates = sets(2, &[&[0, 1][..], &[0]]);
+
+        assert_eq!(resolve(&candidates, 2), Resolution::Unique(vec![1, 0]));
+    }
+
+    #[test]
+    fn ambiguous() {
+        let candidates = sets(3, &[&[0, 1][..], &[0, 1], &[2]]);
+
+        assert_eq!(
+            resolve(&candidates, 3),
+            Resolution::Ambiguous {
+                assignment: vec![0, 1, 2],
+                alternatives: vec![(0, vec![1]), (1, vec![0])],
+            }
+        );
+
+        // A spare rule gives column 0 somewhere else to go.
+        let candidates = sets(3, &[&[0, 2][..], &[1]]);
+        assert_eq!(
+            resolve(&candidates, 3),
+            Resolution::Ambiguous {
+                assignment: vec![0, 1],
+                alternatives: vec![(0, vec![2])],
+            }
+        );
+    }
+
+    #[test]
+    fn impossible() {
+        let candidates = sets(3, &[&[0][..], &[0], &[1, 2]]);
+
+        assert_eq!(
+            resolve(&candidates, 3),
+            Resolution::Impossible {
+                assignment: vec![Some(0), None, Some(1)],
+                unmatched: vec![1],
+            }
+        );
+    }
+}
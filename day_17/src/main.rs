@@ -433,16 +433,25 @@ const INPUT: [&str; 8] = [
     ".....#..",
 ];
 
-#[rustfmt::skip]
-const OFFSETS: [(isize, isize); 9] = [
-    (-1, -1), (-1, 0), (-1, 1),
-    ( 0, -1), ( 0, 0), ( 0, 1),
-    ( 1, -1), ( 1, 0), ( 1, 1),
-];
+mod pocket;
 
-use std::collections::HashMap;
+use pocket::Pocket;
+use std::time::Instant;
 
 fn main() {
+    // cargo run --release --bin day_17 -- <dimensions> [cycles]
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        match run(&args) {
+            Ok(active) => println!("Active: {}", active),
+            Err(error) => {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
     let part_1 = part_1(&INPUT);
     assert_eq!(part_1, 273);
     println!("Part 1: {}", part_1);
@@ -453,229 +462,52 @@ fn main() {
 }
 
 fn part_1(input: &[&str]) -> usize {
-    (0..6).fold(Universe::new(&input), Universe::cycle).active()
+    (0..6).fold(Pocket::<3>::new(input), Pocket::cycle).active()
 }
 
 fn part_2(input: &[&str]) -> usize {
-    (0..6)
-        .fold(Universe4d::new(&input), Universe4d::cycle)
-        .active()
-}
-
-#[derive(PartialEq, Eq, Hash, Clone)]
-struct Vec4 {
-    x: isize,
-    y: isize,
-    z: isize,
-    w: isize,
-}
-
-impl Vec4 {
-    fn new(x: isize, y: isize, z: isize, w: isize) -> Self {
-        Self { x, y, z, w }
-    }
-}
-
-#[derive(Clone)]
-struct Universe4d {
-    cells: HashMap<Vec4, bool>,
-}
-
-impl Universe4d {
-    fn new(input: &[&str]) -> Self {
-        let mut cells = HashMap::new();
-        for (y, line) in input.iter().enumerate() {
-            for (x, c) in line.chars().enumerate() {
-                cells.insert(
-                    Vec4::new(x as _, y as _, 0, 0),
-                    match c {
-                        '#' => true,
-                        '.' => false,
-                        _ => panic!("invalid char"),
-                    },
-                );
-            }
-        }
-        Self { cells }
-    }
-
-    fn bounds(&self) -> (isize, isize, isize, isize, isize, isize, isize, isize) {
-        let x_min = self.cells.keys().map(|c| c.x).min().unwrap() - 1;
-        let x_max = self.cells.keys().map(|c| c.x).max().unwrap() + 1;
-        let y_min = self.cells.keys().map(|c| c.y).min().unwrap() - 1;
-        let y_max = self.cells.keys().map(|c| c.y).max().unwrap() + 1;
-        let z_min = self.cells.keys().map(|c| c.z).min().unwrap() - 1;
-        let z_max = self.cells.keys().map(|c| c.z).max().unwrap() + 1;
-        let w_min = self.cells.keys().map(|c| c.w).min().unwrap() - 1;
-        let w_max = self.cells.keys().map(|c| c.w).max().unwrap() + 1;
-
-        (x_min, x_max, y_min, y_max, z_min, z_max, w_min, w_max)
-    }
-
-    fn cycle(mut self, _: usize) -> Self {
-        let old = self.clone();
-
-        let (x_min, x_max, y_min, y_max, z_min, z_max, w_min, w_max) = self.bounds();
-
-        for w in w_min..=w_max {
-            for z in z_min..=z_max {
-                for y in y_min..=y_max {
-                    for x in x_min..=x_max {
-                        let idx = Vec4::new(x, y, z, w);
-                        let count = old.neighbors(&idx);
-
-                        // If a cube is active and exactly 2 or 3 of its neighbors are also active, the
-                        // cube remains active. Otherwise, the cube becomes inactive.
-                        if *old.cells.get(&idx).unwrap_or(&false) && count != 2 && count != 3 {
-                            self.cells.insert(idx, false);
-                        // If a cube is inactive but exactly 3 of its neighbors are active, the cube
-                        // becomes active. Otherwise, the cube remains inactive.
-                        } else if count == 3 {
-                            self.cells.insert(idx, true);
-                        }
-                    }
-                }
-            }
-        }
-
-        self
-    }
-
-    fn active(&self) -> usize {
-        self.cells.values().filter(|&&c| c).count()
-    }
-
-    fn neighbors(&self, cell: &Vec4) -> usize {
-        let mut count = 0;
-        for w in -1..=1 {
-            for z in -1..=1 {
-                for (x, y) in &OFFSETS {
-                    let idx = Vec4::new(cell.x + *x, cell.y + *y, cell.z + z, cell.w + w);
-
-                    if cell != &idx && *self.cells.get(&idx).unwrap_or(&false) {
-                        count += 1;
-                    }
-                }
-            }
-        }
-        count
-    }
-}
-
-#[derive(PartialEq, Eq, Hash, Clone)]
-struct Vec3 {
-    x: isize,
-    y: isize,
-    z: isize,
+    (0..6).fold(Pocket::<4>::new(input), Pocket::cycle).active()
 }
 
-impl Vec3 {
-    fn new(x: isize, y: isize, z: isize) -> Self {
-        Self { x, y, z }
-    }
-}
-
-#[derive(Clone)]
-struct Universe {
-    cells: HashMap<Vec3, bool>,
+fn run(args: &[String]) -> Result<usize, String> {
+    let number = |arg: &String| -> Result<usize, String> {
+        arg.parse().map_err(|_| format!("not a number: {}", arg))
+    };
+
+    let (dimensions, cycles) = match args {
+        [dimensions] => (number(dimensions)?, 6),
+        [dimensions, cycles] => (number(dimensions)?, number(cycles)?),
+        _ => return Err("usage: day_17 <dimensions> [cycles]".to_owned()),
+    };
+
+    let active = match dimensions {
+        2 => timed::<2>(cycles),
+        3 => timed::<3>(cycles),
+        4 => timed::<4>(cycles),
+        5 => timed::<5>(cycles),
+        6 => timed::<6>(cycles),
+        _ => return Err("dimensions must be between 2 and 6".to_owned()),
+    };
+
+    Ok(active)
 }
 
-impl Universe {
-    fn new(input: &[&str]) -> Self {
-        let mut cells = HashMap::new();
-        for (y, line) in input.iter().enumerate() {
-            for (x, c) in line.chars().enumerate() {
-                cells.insert(
-                    Vec3::new(x as _, y as _, 0),
-                    match c {
-                        '#' => true,
-                        '.' => false,
-                        _ => panic!("invalid char"),
-                    },
-                );
-            }
-        }
-        Self { cells }
-    }
-
-    fn bounds(&self) -> (isize, isize, isize, isize, isize, isize) {
-        let x_min = self.cells.keys().map(|c| c.x).min().unwrap() - 1;
-        let x_max = self.cells.keys().map(|c| c.x).max().unwrap() + 1;
-        let y_min = self.cells.keys().map(|c| c.y).min().unwrap() - 1;
-        let y_max = self.cells.keys().map(|c| c.y).max().unwrap() + 1;
-        let z_min = self.cells.keys().map(|c| c.z).min().unwrap() - 1;
-        let z_max = self.cells.keys().map(|c| c.z).max().unwrap() + 1;
-
-        (x_min, x_max, y_min, y_max, z_min, z_max)
-    }
-
-    fn cycle(mut self, _: usize) -> Self {
-        let old = self.clone();
-
-        let (x_min, x_max, y_min, y_max, z_min, z_max) = self.bounds();
-
-        for z in z_min..=z_max {
-            for y in y_min..=y_max {
-                for x in x_min..=x_max {
-                    let idx = Vec3::new(x, y, z);
-                    let count = old.neighbors(&idx);
-
-                    // If a cube is active and exactly 2 or 3 of its neighbors are also active, the
-                    // cube remains active. Otherwise, the cube becomes inactive.
-                    if *old.cells.get(&idx).unwrap_or(&false) && count != 2 && count != 3 {
-                        self.cells.insert(idx, false);
-                    // If a cube is inactive but exactly 3 of its neighbors are active, the cube
-                    // becomes active. Otherwise, the cube remains inactive.
-                    } else if count == 3 {
-                        self.cells.insert(idx, true);
-                    }
-                }
-            }
-        }
-
-        self
-    }
-
-    fn active(&self) -> usize {
-        self.cells.values().filter(|&&c| c).count()
-    }
-
-    fn neighbors(&self, cell: &Vec3) -> usize {
-        let mut count = 0;
-        for z in -1..=1 {
-            for (x, y) in &OFFSETS {
-                let idx = Vec3::new(cell.x + *x, cell.y + *y, cell.z + z);
-
-                if cell != &idx && *self.cells.get(&idx).unwrap_or(&false) {
-                    count += 1;
-                }
-            }
-        }
-        count
+// Runs the cycles, printing how long each generation took.
+fn timed<const D: usize>(cycles: usize) -> usize {
+    let mut pocket = Pocket::<D>::new(&INPUT);
+
+    for generation in 1..=cycles {
+        let start = Instant::now();
+        pocket = pocket.cycle(generation);
+        println!(
+            "Generation {}: {} active in {:?}",
+            generation,
+            pocket.active(),
+            start.elapsed()
+        );
     }
-}
-
-impl std::fmt::Debug for Universe {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
-        let (x_min, x_max, y_min, y_max, z_min, z_max) = self.bounds();
-
-        for z in (z_min + 1)..z_max {
-            writeln!(f, "z={}", z)?;
-
-            for y in (y_min + 1)..y_max {
-                for x in (x_min + 1)..x_max {
-                    if *self.cells.get(&Vec3::new(x, y, z)).unwrap_or(&false) {
-                        write!(f, "#")?;
-                    } else {
-                        write!(f, ".")?;
-                    }
-                }
-                writeln!(f)?;
-            }
-        }
 
-        Ok(())
-    }
+    pocket.active()
 }
 
 #[cfg(test)]
@@ -692,22 +524,6 @@ mod tests {
         ];
 
         assert_eq!(part_1(&input), 112);
-    }
-
-    #[test]
-    fn test_neighbors() {
-        #[rustfmt::skip]
-        let input: [&str; 3] = [
-            "#..",
-            "...",
-            "...",
-        ];
-
-        let universe = Universe::new(&input);
-
-        assert_eq!(universe.neighbors(&Vec3::new(0, 0, 0)), 0);
-        assert_eq!(universe.neighbors(&Vec3::new(1, 0, 0)), 1);
-        assert_eq!(universe.neighbors(&Vec3::new(0, 1, 0)), 1);
-        assert_eq!(universe.neighbors(&Vec3::new(0, 0, 1)), 1);
+        assert_eq!(part_2(&input), 848);
     }
 }
@@ -0,0 +1,231 @@
+// A pocket dimension with any number of dimensions D. The first two coordinates are x and y. The
+// starting slice sits at 0 in every higher coordinate and the rules treat +1 and -1 the same, so
+// the state is always symmetric under flipping the sign of any higher coordinate. Only cells with
+// every higher coordinate >= 0 are stored, and each one stands for 2^k cells, where k is how many
+// of its higher coordinates are non-zero.
+//
+// Each cycle, every active cell adds one to the count of each of its neighbours, so only cells
+// next to an active one are ever looked at.
+
+use std::collections::{HashMap, HashSet};
+
+pub type Cell<const D: usize> = [isize; D];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pocket<const D: usize> {
+    active: HashSet<Cell<D>>,
+}
+
+impl<const D: usize> Pocket<D> {
+    pub fn new(input: &[&str]) -> Self {
+        assert!(D >= 2, "a pocket dimension needs at least x and y");
+
+        let mut active = HashSet::new();
+        for (y, line) in input.iter().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                match c {
+                    '#' => {
+                        let mut cell = [0; D];
+                        cell[0] = x as isize;
+                        cell[1] = y as isize;
+                        active.insert(cell);
+                    }
+                    '.' => {}
+                    _ => panic!("invalid char"),
+                }
+            }
+        }
+
+        Self { active }
+    }
+
+    pub fn cycle(self, _: usize) -> Self {
+        let active = self
+            .neighbor_counts(&offsets::<D>())
+            .into_iter()
+            // If a cube is active and exactly 2 or 3 of its neighbors are also active, the cube
+            // remains active. If a cube is inactive but exactly 3 of its neighbors are active, the
+            // cube becomes active. Otherwise, the cube is inactive.
+            .filter(|(cell, count)| *count == 3 || (*count == 2 && self.active.contains(cell)))
+            .map(|(cell, _)| cell)
+            .collect();
+
+        Self { active }
+    }
+
+    // Active cubes in the whole space, counting every mirror image.
+    pub fn active(&self) -> usize {
+        self.active.iter().map(multiplicity).sum()
+    }
+
+    // Active neighbours of a stored cell, in the whole space.
+    #[cfg(test)]
+    fn neighbors(&self, cell: &Cell<D>) -> usize {
+        let counts = self.neighbor_counts(&offsets::<D>());
+        counts.get(cell).copied().unwrap_or(0)
+    }
+
+    // Active neighbours of every stored cell that has any. A stored cell's neighbours can include
+    // mirror images of active cells, so each active cell pushes from every image that's next to a
+    // stored cell: flipping a higher coordinate of 1 to -1 keeps it within reach of 0, flipping
+    // anything larger doesn't.
+    fn neighbor_counts(&self, offsets: &[Cell<D>]) -> HashMap<Cell<D>, usize> {
+        let mut counts = HashMap::new();
+
+        for cell in &self.active {
+            let ones: Vec<_> = (2..D).filter(|i| cell[*i] == 1).collect();
+
+            for flips in 0..1 << ones.len() {
+                let mut image = *cell;
+                for (bit, i) in ones.iter().enumerate() {
+                    if flips & (1 << bit) != 0 {
+                        image[*i] = -1;
+                    }
+                }
+
+                for offset in offsets {
+                    let mut neighbor = image;
+                    for (n, o) in neighbor.iter_mut().zip(offset) {
+                        *n += o;
+                    }
+
+                    if neighbor[2..].iter().all(|n| *n >= 0) {
+                        *counts.entry(neighbor).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+
+        counts
+    }
+}
+
+fn multiplicity<const D: usize>(cell: &Cell<D>) -> usize {
+    1 << cell[2..].iter().filter(|n| **n != 0).count()
+}
+
+// Every offset to a neighbouring cell, all 3^D - 1 of them.
+fn offsets<const D: usize>() -> Vec<Cell<D>> {
+    let (min, max) = ([-1; D], [1; D]);
+    let mut offsets = Vec::new();
+
+    let mut offset = min;
+    loop {
+        if offset != [0; D] {
+            offsets.push(offset);
+        }
+        if !next_cell(&mut offset, &min, &max) {
+            break;
+        }
+    }
+
+    offsets
+}
+
+// Steps through every cell from `min` to `max` like an odometer, returning false after the last.
+fn next_cell<const D: usize>(cell: &mut Cell<D>, min: &Cell<D>, max: &Cell<D>) -> bool {
+    for i in 0..D {
+        if cell[i] < max[i] {
+            cell[i] += 1;
+            return true;
+        }
+        cell[i] = min[i];
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[rustfmt::skip]
+    const EXAMPLE: [&str; 3] = [
+        ".#.",
+        "..#",
+        "###",
+    ];
+
+    // Runs the rules over the whole space, without folding.
+    fn unfolded<const D: usize>(input: &[&str], cycles: usize) -> usize {
+        let mut active: HashSet<Cell<D>> = Pocket::<D>::new(input).active;
+        let offsets = offsets::<D>();
+
+        for _ in 0..cycles {
+            let candidates: HashSet<Cell<D>> = active
+                .iter()
+                .flat_map(|cell| {
+                    offsets.iter().map(move |offset| {
+                        let mut neighbor = *cell;
+                        for i in 0..D {
+                            neighbor[i] += offset[i];
+                        }
+                        neighbor
+                    })
+                })
+                .collect();
+
+            active = candidates
+                .into_iter()
+                .filter(|cell| {
+                    let count = offsets
+                        .iter()
+                        .filter(|offset| {
+                            let mut neighbor = *cell;
+                            for i in 0..D {
+                                neighbor[i] += offset[i];
+                            }
+                            active.contains(&neighbor)
+                        })
+                        .count();
+                    count == 3 || (count == 2 && active.contains(cell))
+                })
+                .collect();
+        }
+
+        active.len()
+    }
+
+    fn run<const D: usize>(input: &[&str], cycles: usize) -> usize {
+        (0..cycles)
+            .fold(Pocket::<D>::new(input), Pocket::cycle)
+            .active()
+    }
+
+    #[test]
+    fn readme_examples() {
+        assert_eq!(run::<3>(&EXAMPLE, 6), 112);
+        assert_eq!(run::<4>(&EXAMPLE, 6), 848);
+    }
+
+    #[test]
+    fn matches_unfolded() {
+        assert_eq!(run::<2>(&EXAMPLE, 4), unfolded::<2>(&EXAMPLE, 4));
+        assert_eq!(run::<3>(&EXAMPLE, 3), unfolded::<3>(&EXAMPLE, 3));
+        assert_eq!(run::<5>(&EXAMPLE, 2), unfolded::<5>(&EXAMPLE, 2));
+    }
+
+    #[test]
+    fn test_neighbors() {
+        #[rustfmt::skip]
+        let input: [&str; 3] = [
+            "#..",
+            "...",
+            "...",
+        ];
+
+        let pocket = Pocket::<3>::new(&input);
+
+        assert_eq!(pocket.neighbors(&[0, 0, 0]), 0);
+        assert_eq!(pocket.neighbors(&[1, 0, 0]), 1);
+        assert_eq!(pocket.neighbors(&[0, 1, 0]), 1);
+        assert_eq!(pocket.neighbors(&[0, 0, 1]), 1);
+
+        // Both z = 1 and its mirror image z = -1 neighbour z = 0.
+        let pocket = pocket.cycle(0);
+        assert_eq!(pocket.active(), 0);
+        let mut pocket = Pocket::<3>::new(&input);
+        pocket.active.insert([0, 0, 1]);
+        assert_eq!(pocket.active(), 3);
+        assert_eq!(pocket.neighbors(&[1, 0, 0]), 3);
+    }
+}
@@ -132,13 +132,19 @@
 // Count the number of valid passports - those that have all required fields and valid values.
 // Continue to treat cid as optional. In your batch file, how many passports are valid?
 
-use std::collections::{HashMap, HashSet};
+mod passport;
+mod schema;
+
+use passport::{Passport, PassportError};
+use schema::Schema;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
 
 fn main() {
-    let input: Vec<_> = include_str!("../input")
-        .split("\n\n")
-        .map(|l| l.to_owned())
-        .collect();
+    let file = File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/input")).unwrap();
+    let input = parse(BufReader::new(file)).unwrap_or_else(|error| panic!("{}", error));
 
     let part_1 = part_1(&input);
     assert_eq!(part_1, 204);
@@ -147,127 +153,80 @@ fn main() {
     let part_2 = part_2(&input);
     assert_eq!(part_2, 179);
     println!("Part 2: {}", part_2);
-}
-
-fn validate_passports<F>(input: &[String], f: F) -> usize
-where
-    F: Fn(HashMap<&str, &str>) -> bool,
-{
-    let valid = input.iter().filter(|passport| {
-        let passport: HashMap<_, _> = passport
-            .split_whitespace()
-            .map(|kv| {
-                let mut iter = kv.split(':');
-                (iter.next().unwrap(), iter.next().unwrap())
-            })
-            .collect();
 
-        f(passport)
-    });
-
-    valid.count()
+    // cargo run --bin day_4 -- <schema file>
+    //
+    // Validates the input against a schema loaded from a config file and reports every failing
+    // field of every invalid passport.
+    if let Some(path) = std::env::args().nth(1) {
+        let config = std::fs::read_to_string(&path).expect("unable to read schema");
+        let schema = match Schema::parse(&config) {
+            Ok(schema) => schema,
+            Err(error) => {
+                eprintln!("{}: {}", path, error);
+                std::process::exit(1);
+            }
+        };
+        report(&input, &schema);
+    }
 }
 
-fn part_1(input: &[String]) -> usize {
-    let required_fields: HashSet<_> = vec!["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"]
-        .into_iter()
-        .collect();
-
-    validate_passports(input, |passport| {
-        let set: HashSet<&str> = passport.keys().copied().collect();
-        set.is_superset(&required_fields)
-    })
+#[derive(Debug)]
+enum Error {
+    Io(io::Error),
+    Passport(PassportError),
 }
 
-fn part_2(input: &[String]) -> usize {
-    validate_passports(input, |input| {
-        // byr (Birth Year) - four digits; at least 1920 and at most 2002.
-        if let Some(byr) = input.get("byr") {
-            let byr: usize = byr.parse().unwrap();
-            if !(1920..=2002).contains(&byr) {
-                return false;
-            }
-        } else {
-            return false;
-        }
-
-        // iyr (Issue Year) - four digits; at least 2010 and at most 2020.
-        if let Some(iyr) = input.get("iyr") {
-            let iyr: usize = iyr.parse().unwrap();
-            if !(2010..=2020).contains(&iyr) {
-                return false;
-            }
-        } else {
-            return false;
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "{}", error),
+            Error::Passport(error) => write!(f, "{}", error),
         }
+    }
+}
 
-        // eyr (Expiration Year) - four digits; at least 2020 and at most 2030.
-        if let Some(eyr) = input.get("eyr") {
-            let eyr: usize = eyr.parse().unwrap();
-            if !(2020..=2030).contains(&eyr) {
-                return false;
-            }
-        } else {
-            return false;
-        }
+fn parse<R: BufRead>(reader: R) -> Result<Vec<Passport>, Error> {
+    records::records(reader)
+        .map(|record| {
+            let record = record.map_err(Error::Io)?;
+            Passport::parse(&record).map_err(Error::Passport)
+        })
+        .collect()
+}
 
-        // hgt (Height) - a number followed by either cm or in:
-        //     If cm, the number must be at least 150 and at most 193.
-        //     If in, the number must be at least 59 and at most 76.
-        if let Some(hgt) = input.get("hgt") {
-            if let Some(cm) = hgt.strip_suffix("cm") {
-                let cm: usize = cm.parse().unwrap();
-                if !(150..=193).contains(&cm) {
-                    return false;
-                }
-            } else if let Some(inches) = hgt.strip_suffix("in") {
-                let inches: usize = inches.parse().unwrap();
-                if !(59..=76).contains(&inches) {
-                    return false;
-                }
-            } else {
-                return false;
+fn report(input: &[Passport], schema: &Schema) {
+    for passport in input {
+        if let Err(errors) = schema.validate(passport) {
+            println!("Passport on line {}:", passport.line);
+            for error in errors {
+                println!("    {}", error);
             }
-        } else {
-            return false;
         }
+    }
+}
 
-        // hcl (Hair Color) - a # followed by exactly six characters 0-9 or a-f.
-        if let Some(hcl) = input.get("hcl") {
-            if let Some(hcl) = hcl.strip_prefix('#') {
-                if hcl.matches(|c: char| c.is_ascii_hexdigit()).count() != 6 {
-                    return false;
-                }
-            } else {
-                return false;
-            }
-        } else {
-            return false;
-        }
+fn part_1(input: &[Passport]) -> usize {
+    let schema = schema::puzzle_schema();
+    let required_fields: HashSet<_> = schema.field_names().collect();
 
-        // ecl (Eye Color) - exactly one of: amb blu brn gry grn hzl oth.
-        if let Some(ecl) = input.get("ecl") {
-            if !["amb", "blu", "brn", "gry", "grn", "hzl", "oth"]
-                .as_ref()
-                .contains(ecl)
-            {
-                return false;
-            }
-        } else {
-            return false;
-        }
+    input
+        .iter()
+        .filter(|passport| {
+            required_fields
+                .iter()
+                .all(|field| passport.get(field).is_some())
+        })
+        .count()
+}
 
-        // pid (Passport ID) - a nine-digit number, including leading zeroes.
-        if let Some(pid) = input.get("pid") {
-            if pid.matches(char::is_numeric).count() != 9 {
-                return false;
-            }
-        } else {
-            return false;
-        }
+fn part_2(input: &[Passport]) -> usize {
+    let schema = schema::puzzle_schema();
 
-        true
-    })
+    input
+        .iter()
+        .filter(|passport| schema.validate(passport).is_ok())
+        .count()
 }
 
 #[cfg(test)]
@@ -276,27 +235,72 @@ mod tests {
 
     #[test]
     fn readme_example() {
-        let input: Vec<_> = vec![
-            // 1
-            "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
-            byr:1937 iyr:2017 cid:147 hgt:183cm",
-            // 2
-            "iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884
-            hcl:#cfa07d byr:1929",
-            // 3 (Special Rule)
-            "hcl:#ae17e1 iyr:2013
-            eyr:2024
-            ecl:brn pid:760753108 byr:1931
-            hgt:179cm",
-            // 4
-            "hcl:#cfa07d eyr:2025 pid:166559648
-            iyr:2011 ecl:brn hgt:59in",
-        ]
-        .iter()
-        .map(|s| s.to_string())
-        .collect();
+        let input = "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
+byr:1937 iyr:2017 cid:147 hgt:183cm
+
+iyr:2013 ecl:amb cid:350 eyr:2023 pid:028048884
+hcl:#cfa07d byr:1929
+
+hcl:#ae17e1 iyr:2013
+eyr:2024
+ecl:brn pid:760753108 byr:1931
+hgt:179cm
+
+hcl:#cfa07d eyr:2025 pid:166559648
+iyr:2011 ecl:brn hgt:59in";
 
         // According to the above rules, your improved system would report 2 valid passports.
-        assert_eq!(part_1(&input), 2);
+        assert_eq!(part_1(&parse(input.as_bytes()).unwrap()), 2);
+
+        // Windows line endings and trailing blank lines make no difference.
+        let crlf = format!("{}\n\n\n", input).replace('\n', "\r\n");
+        assert_eq!(
+            parse(crlf.as_bytes()).unwrap(),
+            parse(input.as_bytes()).unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse("byr:1937\n\niyr:2017 hgt\n".as_bytes())
+                .unwrap_err()
+                .to_string(),
+            "line 3: \"hgt\" is not a key:value pair"
+        );
+        assert!(matches!(parse(&b"byr:1937\n\xff\n"[..]), Err(Error::Io(_))));
+    }
+
+    #[test]
+    fn readme_example_part_2() {
+        let invalid = "eyr:1972 cid:100
+hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926
+
+iyr:2019
+hcl:#602927 eyr:1967 hgt:170cm
+ecl:grn pid:012533040 byr:1946
+
+hcl:dab227 iyr:2012
+ecl:brn hgt:182cm pid:021572410 eyr:2020 byr:1992 cid:277
+
+hgt:59cm ecl:zzz
+eyr:2038 hcl:74454a iyr:2023
+pid:3556412378 byr:2007";
+
+        let valid = "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
+hcl:#623a2f
+
+eyr:2029 ecl:blu cid:129 byr:1989
+iyr:2014 pid:896056539 hcl:#a97842 hgt:165cm
+
+hcl:#888785
+hgt:164cm byr:2001 iyr:2015 cid:88
+pid:545766238 ecl:hzl
+eyr:2022
+
+iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719";
+
+        assert_eq!(part_2(&parse(invalid.as_bytes()).unwrap()), 0);
+        assert_eq!(part_2(&parse(valid.as_bytes()).unwrap()), 4);
     }
 }
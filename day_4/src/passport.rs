@@ -0,0 +1,170 @@
+use records::Record;
+use std::fmt;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Passport {
+    // Line the passport starts on in the batch file.
+    pub line: usize,
+    pub byr: Option<String>,
+    pub iyr: Option<String>,
+    pub eyr: Option<String>,
+    pub hgt: Option<String>,
+    pub hcl: Option<String>,
+    pub ecl: Option<String>,
+    pub pid: Option<String>,
+    pub cid: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PassportError {
+    // A word that isn't a key:value pair.
+    MissingSeparator { line: usize, word: String },
+    UnknownField { line: usize, key: String },
+    DuplicateField { line: usize, key: String },
+}
+
+impl fmt::Display for PassportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PassportError::MissingSeparator { line, word } => {
+                write!(f, "line {}: {:?} is not a key:value pair", line, word)
+            }
+            PassportError::UnknownField { line, key } => {
+                write!(f, "line {}: unknown field {:?}", line, key)
+            }
+            PassportError::DuplicateField { line, key } => {
+                write!(f, "line {}: duplicate field {:?}", line, key)
+            }
+        }
+    }
+}
+
+impl Passport {
+    pub fn parse(record: &Record) -> Result<Self, PassportError> {
+        let mut passport = Passport {
+            line: record.line,
+            ..Passport::default()
+        };
+
+        for (line, text) in record.numbered_lines() {
+            for word in text.split_whitespace() {
+                let mut iter = word.splitn(2, ':');
+                let key = iter.next().unwrap();
+                let value = iter.next().ok_or_else(|| PassportError::MissingSeparator {
+                    line,
+                    word: word.to_owned(),
+                })?;
+
+                let field = passport
+                    .field_mut(key)
+                    .ok_or_else(|| PassportError::UnknownField {
+                        line,
+                        key: key.to_owned(),
+                    })?;
+
+                if field.is_some() {
+                    return Err(PassportError::DuplicateField {
+                        line,
+                        key: key.to_owned(),
+                    });
+                }
+
+                *field = Some(value.to_owned());
+            }
+        }
+
+        Ok(passport)
+    }
+
+    // Whether `key` is one of the fields a passport can hold.
+    pub fn has_field(key: &str) -> bool {
+        Passport::default().field_mut(key).is_some()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        let field = match key {
+            "byr" => &self.byr,
+            "iyr" => &self.iyr,
+            "eyr" => &self.eyr,
+            "hgt" => &self.hgt,
+            "hcl" => &self.hcl,
+            "ecl" => &self.ecl,
+            "pid" => &self.pid,
+            "cid" => &self.cid,
+            _ => return None,
+        };
+
+        field.as_deref()
+    }
+
+    fn field_mut(&mut self, key: &str) -> Option<&mut Option<String>> {
+        match key {
+            "byr" => Some(&mut self.byr),
+            "iyr" => Some(&mut self.iyr),
+            "eyr" => Some(&mut self.eyr),
+            "hgt" => Some(&mut self.hgt),
+            "hcl" => Some(&mut self.hcl),
+            "ecl" => Some(&mut self.ecl),
+            "pid" => Some(&mut self.pid),
+            "cid" => Some(&mut self.cid),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(line: usize, lines: &[&str]) -> Record {
+        Record {
+            line,
+            lines: lines.iter().map(|l| l.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn parse() {
+        let passport = Passport::parse(&record(
+            3,
+            &[
+                "hcl:#cfa07d eyr:2025 pid:166559648",
+                "iyr:2011 ecl:brn hgt:59in",
+            ],
+        ))
+        .unwrap();
+
+        assert_eq!(passport.line, 3);
+        assert_eq!(passport.hgt.as_deref(), Some("59in"));
+        assert_eq!(passport.get("pid"), Some("166559648"));
+        assert_eq!(passport.get("byr"), None);
+        assert_eq!(passport.get("zzz"), None);
+        assert!(Passport::has_field("cid"));
+        assert!(!Passport::has_field("zzz"));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            Passport::parse(&record(1, &["byr:1937", "iyr:2017 hgt"])),
+            Err(PassportError::MissingSeparator {
+                line: 2,
+                word: "hgt".to_owned()
+            })
+        );
+        assert_eq!(
+            Passport::parse(&record(5, &["byr:1937 zzz:1"])),
+            Err(PassportError::UnknownField {
+                line: 5,
+                key: "zzz".to_owned()
+            })
+        );
+        assert_eq!(
+            Passport::parse(&record(5, &["byr:1937", "", "byr:1938"])),
+            Err(PassportError::DuplicateField {
+                line: 7,
+                key: "byr".to_owned()
+            })
+        );
+    }
+}
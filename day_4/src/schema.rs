@@ -0,0 +1,349 @@
+// A passport schema is a list of required fields, each with a validator. Schemas can be built in
+// code or parsed from a config file with one field per line:
+//
+//     # comments and blank lines are ignored
+//     byr year 1920 2002
+//     hgt height cm 150 193 in 59 76
+//     hcl colour
+//     ecl one-of amb blu brn gry grn hzl oth
+//     pid digits 9
+
+use crate::passport::Passport;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Validator {
+    // Four digits within the range.
+    Year(RangeInclusive<u32>),
+    // A number followed by one of the units, within that unit's range.
+    Height(Vec<(String, RangeInclusive<u32>)>),
+    // A # followed by exactly six characters 0-9 or a-f.
+    HexColour,
+    // Exactly one of the values.
+    OneOf(Vec<String>),
+    // A number with exactly this many digits, including leading zeroes.
+    Digits(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reason {
+    Missing,
+    NotANumber(String),
+    OutOfRange(u32, RangeInclusive<u32>),
+    UnknownUnit(String),
+    NotAColour(String),
+    NotAllowed(String),
+    WrongLength(usize, usize),
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Reason::Missing => write!(f, "missing"),
+            Reason::NotANumber(value) => write!(f, "{:?} is not a number", value),
+            Reason::OutOfRange(value, range) => write!(
+                f,
+                "{} is not between {} and {}",
+                value,
+                range.start(),
+                range.end()
+            ),
+            Reason::UnknownUnit(value) => write!(f, "{:?} has no known unit", value),
+            Reason::NotAColour(value) => write!(f, "{:?} is not a hex colour", value),
+            Reason::NotAllowed(value) => write!(f, "{:?} is not an allowed value", value),
+            Reason::WrongLength(expected, actual) => {
+                write!(f, "expected {} digits, got {}", expected, actual)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub reason: Reason,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.reason)
+    }
+}
+
+impl Validator {
+    fn validate(&self, value: &str) -> Result<(), Reason> {
+        match self {
+            Validator::Year(range) => {
+                if value.len() != 4 {
+                    return Err(Reason::WrongLength(4, value.len()));
+                }
+                in_range(value, range)
+            }
+            Validator::Height(units) => units
+                .iter()
+                .find_map(|(unit, range)| {
+                    value
+                        .strip_suffix(unit.as_str())
+                        .map(|number| in_range(number, range))
+                })
+                .unwrap_or_else(|| Err(Reason::UnknownUnit(value.to_owned()))),
+            Validator::HexColour => match value.strip_prefix('#') {
+                Some(hex) if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) => Ok(()),
+                _ => Err(Reason::NotAColour(value.to_owned())),
+            },
+            Validator::OneOf(values) => {
+                if values.iter().any(|v| v == value) {
+                    Ok(())
+                } else {
+                    Err(Reason::NotAllowed(value.to_owned()))
+                }
+            }
+            Validator::Digits(count) => {
+                if !value.chars().all(|c| c.is_ascii_digit()) {
+                    Err(Reason::NotANumber(value.to_owned()))
+                } else if value.len() != *count {
+                    Err(Reason::WrongLength(*count, value.len()))
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+fn in_range(value: &str, range: &RangeInclusive<u32>) -> Result<(), Reason> {
+    // `parse` accepts a leading '+', but a field is only digits.
+    if !value.chars().all(|c| c.is_ascii_digit()) {
+        return Err(Reason::NotANumber(value.to_owned()));
+    }
+
+    let number: u32 = value
+        .parse()
+        .map_err(|_| Reason::NotANumber(value.to_owned()))?;
+
+    if range.contains(&number) {
+        Ok(())
+    } else {
+        Err(Reason::OutOfRange(number, range.clone()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Schema {
+    fields: Vec<(String, Validator)>,
+}
+
+impl Schema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn field(mut self, name: &str, validator: Validator) -> Self {
+        self.fields.push((name.to_owned(), validator));
+        self
+    }
+
+    pub fn field_names(&self) -> impl Iterator<Item = &str> {
+        self.fields.iter().map(|(name, _)| name.as_str())
+    }
+
+    // Checks every field and returns all of the failures, in schema order.
+    pub fn validate(&self, passport: &Passport) -> Result<(), Vec<FieldError>> {
+        let errors: Vec<_> = self
+            .fields
+            .iter()
+            .filter_map(|(name, validator)| {
+                let result = match passport.get(name) {
+                    Some(value) => validator.validate(value),
+                    None => Err(Reason::Missing),
+                };
+
+                result.err().map(|reason| FieldError {
+                    field: name.clone(),
+                    reason,
+                })
+            })
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn parse(input: &str) -> Result<Self, SchemaError> {
+        let mut schema = Schema::new();
+
+        for (index, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = |message: &str| SchemaError {
+                line: index + 1,
+                message: message.to_owned(),
+            };
+
+            let mut words = line.split_whitespace();
+            let name = words.next().unwrap();
+            if !Passport::has_field(name) {
+                return Err(error(&format!("unknown field {:?}", name)));
+            }
+            let kind = words.next().ok_or_else(|| error("missing validator"))?;
+            let args: Vec<_> = words.collect();
+
+            let number = |arg: &str| -> Result<u32, SchemaError> {
+                arg.parse()
+                    .map_err(|_| error(&format!("{:?} is not a number", arg)))
+            };
+
+            let validator = match (kind, args.as_slice()) {
+                ("year", [min, max]) => Validator::Year(number(min)?..=number(max)?),
+                ("height", units) if !units.is_empty() && units.len() % 3 == 0 => {
+                    let units = units
+                        .chunks(3)
+                        .map(|unit| Ok((unit[0].to_owned(), number(unit[1])?..=number(unit[2])?)))
+                        .collect::<Result<_, _>>()?;
+                    Validator::Height(units)
+                }
+                ("colour", []) => Validator::HexColour,
+                ("one-of", values) if !values.is_empty() => {
+                    Validator::OneOf(values.iter().map(|v| v.to_string()).collect())
+                }
+                ("digits", [count]) => Validator::Digits(number(count)? as usize),
+                ("year", _) | ("height", _) | ("colour", _) | ("one-of", _) | ("digits", _) => {
+                    return Err(error(&format!("wrong arguments for {}", kind)));
+                }
+                _ => return Err(error(&format!("unknown validator {:?}", kind))),
+            };
+
+            schema = schema.field(name, validator);
+        }
+
+        Ok(schema)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SchemaError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+// The part 2 rules, as listed in the puzzle.
+pub fn puzzle_schema() -> Schema {
+    let eye_colours = ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
+
+    Schema::new()
+        .field("byr", Validator::Year(1920..=2002))
+        .field("iyr", Validator::Year(2010..=2020))
+        .field("eyr", Validator::Year(2020..=2030))
+        .field(
+            "hgt",
+            Validator::Height(vec![
+                ("cm".to_owned(), 150..=193),
+                ("in".to_owned(), 59..=76),
+            ]),
+        )
+        .field("hcl", Validator::HexColour)
+        .field(
+            "ecl",
+            Validator::OneOf(eye_colours.iter().map(|c| c.to_string()).collect()),
+        )
+        .field("pid", Validator::Digits(9))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_every_failing_field() {
+        let passport = Passport {
+            hgt: Some("59cm".to_owned()),
+            ecl: Some("zzz".to_owned()),
+            eyr: Some("2038".to_owned()),
+            hcl: Some("74454a".to_owned()),
+            iyr: Some("2023".to_owned()),
+            pid: Some("3556412378".to_owned()),
+            byr: Some("twenty".to_owned()),
+            ..Passport::default()
+        };
+
+        let reasons: Vec<_> = puzzle_schema()
+            .validate(&passport)
+            .unwrap_err()
+            .into_iter()
+            .map(|error| (error.field, error.reason))
+            .collect();
+
+        assert_eq!(
+            reasons,
+            vec![
+                ("byr".to_owned(), Reason::WrongLength(4, 6)),
+                ("iyr".to_owned(), Reason::OutOfRange(2023, 2010..=2020)),
+                ("eyr".to_owned(), Reason::OutOfRange(2038, 2020..=2030)),
+                ("hgt".to_owned(), Reason::OutOfRange(59, 150..=193)),
+                ("hcl".to_owned(), Reason::NotAColour("74454a".to_owned())),
+                ("ecl".to_owned(), Reason::NotAllowed("zzz".to_owned())),
+                ("pid".to_owned(), Reason::WrongLength(9, 10)),
+            ]
+        );
+
+        let passport = Passport {
+            hgt: Some("+170cm".to_owned()),
+            ..Passport::default()
+        };
+        let errors = puzzle_schema().validate(&passport).unwrap_err();
+        let hgt = errors.iter().find(|error| error.field == "hgt").unwrap();
+        assert_eq!(hgt.reason, Reason::NotANumber("+170".to_owned()));
+
+        let errors = puzzle_schema().validate(&Passport::default()).unwrap_err();
+        assert_eq!(errors.len(), 7);
+        assert!(errors.iter().all(|error| error.reason == Reason::Missing));
+    }
+
+    #[test]
+    fn parse_config() {
+        let schema = Schema::parse(include_str!("../schema")).unwrap();
+        assert_eq!(schema, puzzle_schema());
+
+        assert_eq!(
+            Schema::parse("byr year 1920\n"),
+            Err(SchemaError {
+                line: 1,
+                message: "wrong arguments for year".to_owned()
+            })
+        );
+        assert_eq!(
+            Schema::parse("# comment\n\npid digits nine\n"),
+            Err(SchemaError {
+                line: 3,
+                message: "\"nine\" is not a number".to_owned()
+            })
+        );
+        assert_eq!(
+            Schema::parse("byr year 1920 2002\nbyrr year 1920 2002\n"),
+            Err(SchemaError {
+                line: 2,
+                message: "unknown field \"byrr\"".to_owned()
+            })
+        );
+        assert_eq!(
+            Schema::parse("pid regex ^[0-9]+$\n"),
+            Err(SchemaError {
+                line: 1,
+                message: "unknown validator \"regex\"".to_owned()
+            })
+        );
+    }
+}
@@ -66,71 +66,271 @@
 //
 // What is the ID of your seat?
 
+use std::fmt;
+
 fn main() {
     let input: Vec<_> = include_str!("../input")
         .lines()
         .map(|l| l.to_owned())
         .collect();
 
-    let part_1 = part_1(&input);
+    let part_1 = part_1(&input).unwrap();
     assert_eq!(part_1, 996);
     println!("Part 1: {}", part_1);
 
-    let part_2 = part_2(&input);
-    assert_eq!(part_2, 671);
-    println!("Part 2: {}", part_2);
+    let part_2 = part_2(&input).unwrap();
+    assert_eq!(part_2, vec![671]);
+    println!("Part 2: {}", part_2[0]);
+
+    let plane = Plane::default();
+    let pass = plane.encode(part_2[0] / plane.cols(), part_2[0] % plane.cols());
+    println!("Boarding pass: {}", pass.unwrap());
+
+    // cargo run --bin day_5 -- <rows> <cols> <letters> <boarding pass>...
+    //
+    // Decodes boarding passes for a plane with a different layout, e.g. "16 4 01ab 0101ba". The
+    // letters are front, back, left, right.
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        if let Err(error) = decode_args(&args) {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn decode_args(args: &[String]) -> Result<(), String> {
+    let (rows, cols, letters, passes) = match args {
+        [rows, cols, letters, passes @ ..] => (rows, cols, letters, passes),
+        _ => return Err("usage: day_5 <rows> <cols> <letters> <boarding pass>...".to_owned()),
+    };
+
+    let rows = rows
+        .parse()
+        .map_err(|_| format!("invalid rows: {}", rows))?;
+    let cols = cols
+        .parse()
+        .map_err(|_| format!("invalid cols: {}", cols))?;
+    let letters: Vec<_> = letters.chars().collect();
+    let (front, back, left, right) = match letters.as_slice() {
+        [front, back, left, right] => (*front, *back, *left, *right),
+        _ => return Err("letters should be front, back, left, right".to_owned()),
+    };
+
+    let plane = Plane::new(rows, cols)
+        .and_then(|plane| plane.with_letters(front, back, left, right))
+        .map_err(|error| error.to_string())?;
+
+    for pass in passes {
+        match plane.decode(pass) {
+            Ok(seat) => println!(
+                "{}: row {}, column {}, seat ID {}",
+                pass, seat.row, seat.col, seat.id
+            ),
+            Err(error) => println!("{}: {}", pass, error),
+        }
+    }
+
+    Ok(())
 }
 
-fn part_1(input: &[String]) -> usize {
-    input.iter().map(|line| seat_id(line)).max().unwrap()
+fn part_1(input: &[String]) -> Result<usize, Error> {
+    let plane = Plane::default();
+
+    input
+        .iter()
+        .map(|line| plane.decode(line).map(|seat| seat.id))
+        .try_fold(0, |max, id| id.map(|id| max.max(id)))
 }
 
-fn part_2(input: &[String]) -> usize {
-    let mut taken_seats: Vec<_> = input.iter().map(|line| seat_id(line)).collect();
+// Every seat ID that is missing between the lowest and highest taken seats.
+fn part_2(input: &[String]) -> Result<Vec<usize>, Error> {
+    let plane = Plane::default();
+
+    let mut taken_seats = input
+        .iter()
+        .map(|line| plane.decode(line).map(|seat| seat.id))
+        .collect::<Result<Vec<_>, _>>()?;
     taken_seats.sort_unstable();
 
-    for seats in taken_seats.windows(2) {
-        let next_seat = seats[0] + 1;
+    let empty_seats: Vec<_> = taken_seats
+        .windows(2)
+        .flat_map(|seats| seats[0] + 1..seats[1])
+        .collect();
 
-        if next_seat == seats[1] {
-            continue;
-        }
+    if empty_seats.is_empty() {
+        Err(Error::NoEmptySeat)
+    } else {
+        Ok(empty_seats)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Error {
+    NotPowerOfTwo(usize),
+    // Seat IDs for this many seats don't fit in a usize.
+    TooManySeats { rows: usize, cols: usize },
+    DuplicateLetter(char),
+    WrongLength { expected: usize, actual: usize },
+    UnknownLetter { letter: char, position: usize },
+    NoSuchSeat { row: usize, col: usize },
+    NoEmptySeat,
+}
 
-        return next_seat;
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::NotPowerOfTwo(n) => write!(f, "{} is not a power of two", n),
+            Error::TooManySeats { rows, cols } => {
+                write!(f, "{} rows of {} seats is too many to number", rows, cols)
+            }
+            Error::DuplicateLetter(c) => write!(f, "{:?} is used for more than one half", c),
+            Error::WrongLength { expected, actual } => write!(
+                f,
+                "boarding pass should be {} letters, got {}",
+                expected, actual
+            ),
+            Error::UnknownLetter { letter, position } => {
+                write!(f, "unexpected {:?} at position {}", letter, position)
+            }
+            Error::NoSuchSeat { row, col } => write!(f, "no seat at row {}, col {}", row, col),
+            Error::NoEmptySeat => write!(f, "no empty seat"),
+        }
     }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Seat {
+    row: usize,
+    col: usize,
+    id: usize,
+}
 
-    unreachable!();
+// The plane's geometry and the letters used to pick a half at each step. Rows are encoded first,
+// most significant bit first, then columns.
+#[derive(Debug, Clone)]
+struct Plane {
+    row_bits: usize,
+    col_bits: usize,
+    front: char,
+    back: char,
+    left: char,
+    right: char,
 }
 
-fn seat_id(input: &str) -> usize {
-    let mut row_min = 0;
-    let mut row_max = 127;
+impl Default for Plane {
+    // This airline's planes: 128 rows and 8 columns.
+    fn default() -> Self {
+        Self::new(128, 8).unwrap()
+    }
+}
 
-    let mut col_min = 0;
-    let mut col_max = 7;
+impl Plane {
+    fn new(rows: usize, cols: usize) -> Result<Self, Error> {
+        let row_bits = bits(rows)?;
+        let col_bits = bits(cols)?;
+        if row_bits + col_bits > usize::BITS as usize {
+            return Err(Error::TooManySeats { rows, cols });
+        }
 
-    let calc = |min, max| max - ((max - min) / 2);
+        Ok(Self {
+            row_bits,
+            col_bits,
+            front: 'F',
+            back: 'B',
+            left: 'L',
+            right: 'R',
+        })
+    }
 
-    for c in input.chars() {
-        match c {
-            'F' => {
-                row_max = calc(row_min, row_max);
+    fn with_letters(self, front: char, back: char, left: char, right: char) -> Result<Self, Error> {
+        let letters = [front, back, left, right];
+        for (i, c) in letters.iter().enumerate() {
+            if letters[i + 1..].contains(c) {
+                return Err(Error::DuplicateLetter(*c));
             }
-            'B' => {
-                row_min = calc(row_min, row_max);
+        }
+
+        Ok(Self {
+            front,
+            back,
+            left,
+            right,
+            ..self
+        })
+    }
+
+    fn cols(&self) -> usize {
+        1 << self.col_bits
+    }
+
+    fn seat(&self, row: usize, col: usize) -> Result<Seat, Error> {
+        if row >> self.row_bits != 0 || col >> self.col_bits != 0 {
+            return Err(Error::NoSuchSeat { row, col });
+        }
+
+        // Every seat also has a unique seat ID: multiply the row by the number of columns, then
+        // add the col.
+        Ok(Seat {
+            row,
+            col,
+            id: row * self.cols() + col,
+        })
+    }
+
+    fn decode(&self, pass: &str) -> Result<Seat, Error> {
+        let expected = self.row_bits + self.col_bits;
+        let actual = pass.chars().count();
+        if actual != expected {
+            return Err(Error::WrongLength { expected, actual });
+        }
+
+        let mut row = 0;
+        let mut col = 0;
+
+        for (position, letter) in pass.chars().enumerate() {
+            let is_row = position < self.row_bits;
+
+            match letter {
+                c if is_row && c == self.front => row <<= 1,
+                c if is_row && c == self.back => row = row << 1 | 1,
+                c if !is_row && c == self.left => col <<= 1,
+                c if !is_row && c == self.right => col = col << 1 | 1,
+                _ => return Err(Error::UnknownLetter { letter, position }),
             }
-            'R' => {
-                col_min = calc(col_min, col_max);
+        }
+
+        self.seat(row, col)
+    }
+
+    fn encode(&self, row: usize, col: usize) -> Result<String, Error> {
+        self.seat(row, col)?;
+
+        let rows = (0..self.row_bits).rev().map(|bit| {
+            if row >> bit & 1 == 1 {
+                self.back
+            } else {
+                self.front
             }
-            'L' => {
-                col_max = calc(col_min, col_max);
+        });
+        let cols = (0..self.col_bits).rev().map(|bit| {
+            if col >> bit & 1 == 1 {
+                self.right
+            } else {
+                self.left
             }
-            _ => panic!("unknown input: {}", c),
-        }
+        });
+
+        Ok(rows.chain(cols).collect())
     }
+}
 
-    // Every seat also has a unique seat ID: multiply the row by 8, then add the col.
-    row_min * 8 + col_min
+fn bits(n: usize) -> Result<usize, Error> {
+    if n.is_power_of_two() {
+        Ok(n.trailing_zeros() as usize)
+    } else {
+        Err(Error::NotPowerOfTwo(n))
+    }
 }
 
 #[cfg(test)]
@@ -139,13 +339,97 @@ mod tests {
 
     #[test]
     fn readme_example() {
+        let plane = Plane::default();
+
         // BFFFBBFRRR: row 70, column 7, seat ID 567.
-        assert_eq!(seat_id(&"BFFFBBFRRR"), 567);
+        assert_eq!(
+            plane.decode("BFFFBBFRRR"),
+            Ok(Seat {
+                row: 70,
+                col: 7,
+                id: 567
+            })
+        );
 
         // FFFBBBFRRR: row 14, column 7, seat ID 119.
-        assert_eq!(seat_id(&"FFFBBBFRRR"), 119);
+        assert_eq!(plane.decode("FFFBBBFRRR").map(|s| s.id), Ok(119));
 
         // BBFFBBFRLL: row 102, column 4, seat ID 820.
-        assert_eq!(seat_id(&"BBFFBBFRLL"), 820);
+        assert_eq!(plane.decode("BBFFBBFRLL").map(|s| s.id), Ok(820));
+    }
+
+    #[test]
+    fn encode_decode() {
+        let plane = Plane::default();
+
+        assert_eq!(plane.encode(70, 7), Ok("BFFFBBFRRR".to_owned()));
+        assert_eq!(
+            plane.encode(128, 0),
+            Err(Error::NoSuchSeat { row: 128, col: 0 })
+        );
+
+        for row in 0..128 {
+            for col in 0..8 {
+                let pass = plane.encode(row, col).unwrap();
+                assert_eq!(plane.decode(&pass), plane.seat(row, col));
+            }
+        }
+
+        let plane = Plane::new(16, 4)
+            .unwrap()
+            .with_letters('0', '1', 'a', 'b')
+            .unwrap();
+        assert_eq!(plane.encode(5, 2), Ok("0101ba".to_owned()));
+        assert_eq!(plane.decode("0101ba").map(|s| s.id), Ok(22));
+    }
+
+    #[test]
+    fn errors() {
+        let plane = Plane::default();
+
+        assert_eq!(Plane::new(100, 8).unwrap_err(), Error::NotPowerOfTwo(100));
+        assert_eq!(
+            Plane::new(1 << 40, 1 << 40).unwrap_err(),
+            Error::TooManySeats {
+                rows: 1 << 40,
+                cols: 1 << 40
+            }
+        );
+        // Every seat ID fits exactly.
+        let wide = Plane::new(1 << 32, 1 << 32).unwrap();
+        assert_eq!(
+            wide.seat(u32::MAX as usize, u32::MAX as usize)
+                .map(|s| s.id),
+            Ok(usize::MAX)
+        );
+        assert_eq!(
+            plane.clone().with_letters('F', 'B', 'F', 'R').unwrap_err(),
+            Error::DuplicateLetter('F')
+        );
+        assert_eq!(
+            plane.decode("BFFFBBFRR"),
+            Err(Error::WrongLength {
+                expected: 10,
+                actual: 9
+            })
+        );
+        assert_eq!(
+            plane.decode("BFFFBBFRRB"),
+            Err(Error::UnknownLetter {
+                letter: 'B',
+                position: 9
+            })
+        );
+    }
+
+    #[test]
+    fn empty_seats() {
+        let input: Vec<_> = ["FFFFFFFLLL", "FFFFFFFLRR", "FFFFFFFRLR", "FFFFFFFRRR"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        assert_eq!(part_2(&input), Ok(vec![1, 2, 4, 6]));
+        assert_eq!(part_2(&input[..1]), Err(Error::NoEmptySeat));
     }
 }
@@ -94,13 +94,14 @@
 // For each group, count the number of questions to which everyone answered "yes". What is the sum
 // of those counts?
 
-use std::collections::{HashMap, HashSet};
+use records::Record;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
 
 fn main() {
-    let input: Vec<_> = include_str!("../input")
-        .split("\n\n")
-        .map(|l| l.to_owned())
-        .collect();
+    let file = File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/input")).unwrap();
+    let input = parse(BufReader::new(file)).unwrap_or_else(|error| panic!("{}", error));
 
     let part_1 = part_1(&input);
     assert_eq!(part_1, 6387);
@@ -109,54 +110,138 @@ fn main() {
     let part_2 = part_2(&input);
     assert_eq!(part_2, 3039);
     println!("Part 2: {}", part_2);
+
+    let lonely: usize = input.iter().map(|group| group.exactly(1).len()).sum();
+    println!("Answered by exactly one person in their group: {}", lonely);
 }
 
-fn part_1(input: &[String]) -> usize {
-    input
-        .iter()
-        .map(|group| {
-            group
-                .chars()
-                .filter(char::is_ascii_alphabetic)
-                .collect::<HashSet<_>>()
-                .len()
-        })
-        .sum()
+fn parse<R: BufRead>(reader: R) -> Result<Vec<Group>, Error> {
+    records::records(reader)
+        .map(|record| Group::parse(&record.map_err(Error::Io)?))
+        .collect()
+}
+
+fn part_1(input: &[Group]) -> usize {
+    input.iter().map(|group| group.any().len()).sum()
+}
+
+fn part_2(input: &[Group]) -> usize {
+    input.iter().map(|group| group.all().len()).sum()
+}
+
+// The questions (a-z) someone answered "yes" to, one bit per question.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+struct Answers(u32);
+
+impl Answers {
+    const ALL: Answers = Answers((1 << 26) - 1);
+
+    fn parse(line: usize, person: &str) -> Result<Self, Error> {
+        person
+            .char_indices()
+            .try_fold(Answers::default(), |answers, (column, c)| {
+                if c.is_ascii_lowercase() {
+                    Ok(Answers(answers.0 | 1 << (c as u8 - b'a')))
+                } else {
+                    Err(Error::Question {
+                        line,
+                        column: column + 1,
+                        character: c,
+                    })
+                }
+            })
+    }
+
+    fn contains(self, question: usize) -> bool {
+        self.0 >> question & 1 == 1
+    }
+
+    fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Group {
+    people: Vec<Answers>,
 }
 
-fn part_2(input: &[String]) -> usize {
-    input
-        .iter()
-        .map(|group| {
-            // abc => [abc]
-            //
-            // a
-            // b
-            // c => [a, b, c]
-            //
-            // ab
-            // ac => [ab, ac]
-            let people: Vec<_> = group.split_whitespace().collect();
-
-            // For each person
-            let mut map = people.iter().fold(HashMap::new(), |mut map, person| {
-                // iterate through their votes
-                for c in person.chars() {
-                    // and count how many votes each char got
-                    let count = map.entry(c).or_insert(0);
+impl Group {
+    fn parse(record: &Record) -> Result<Self, Error> {
+        let people = record
+            .numbered_lines()
+            .map(|(line, person)| Answers::parse(line, person.trim()))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { people })
+    }
+
+    // Questions anyone in the group answered "yes" to.
+    fn any(&self) -> Answers {
+        Answers(self.people.iter().fold(0, |acc, person| acc | person.0))
+    }
+
+    // Questions everyone in the group answered "yes" to.
+    fn all(&self) -> Answers {
+        Answers(
+            self.people
+                .iter()
+                .fold(Answers::ALL.0, |acc, person| acc & person.0),
+        )
+    }
+
+    // Questions exactly `k` people in the group answered "yes" to.
+    fn exactly(&self, k: usize) -> Answers {
+        let histogram = self.histogram();
+
+        Answers(
+            (0..26)
+                .filter(|q| histogram[*q] == k)
+                .fold(0, |acc, q| acc | 1 << q),
+        )
+    }
+
+    // How many people answered "yes" to each question, a-z.
+    fn histogram(&self) -> [usize; 26] {
+        let mut histogram = [0; 26];
+
+        for person in &self.people {
+            for (question, count) in histogram.iter_mut().enumerate() {
+                if person.contains(question) {
                     *count += 1;
                 }
-                map
-            });
+            }
+        }
 
-            let count = people.len();
+        histogram
+    }
+}
 
-            // Only retain the chars where all people (count) voted
-            map.retain(|_k, v| v == &count);
+#[derive(Debug)]
+enum Error {
+    Io(io::Error),
+    Question {
+        line: usize,
+        column: usize,
+        character: char,
+    },
+}
 
-            map.len()
-        })
-        .sum()
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "{}", error),
+            Error::Question {
+                line,
+                column,
+                character,
+            } => write!(
+                f,
+                "line {}, column {}: {:?} is not a question",
+                line, column, character
+            ),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -180,26 +265,25 @@ mod tests {
         // a
         //
         // b
-        let input = vec![
-            "abc".to_string(),
-            // -
-            "a
-            b
-            c"
-            .to_string(),
-            // -
-            "ab
-            ac"
-            .to_string(),
-            // -
-            "a
-             a
-             a
-             a"
-            .to_string(),
-            // -
-            "b".to_string(),
-        ];
+        let input = parse(
+            "abc
+
+a
+b
+c
+
+ab
+ac
+
+a
+a
+a
+a
+
+b"
+            .as_bytes(),
+        )
+        .unwrap();
 
         // This list represents answers from five groups:
         //
@@ -223,4 +307,42 @@ mod tests {
         //     - In the fifth group, everyone (all 1 person) answered "yes" to 1 question, b.
         assert_eq!(part_2(&input), 6);
     }
+
+    #[test]
+    fn group_queries() {
+        let group = Group::parse(&Record {
+            line: 1,
+            lines: vec!["abc".to_owned(), "abd".to_owned(), "ae".to_owned()],
+        })
+        .unwrap();
+
+        let letters = |answers: Answers| -> String {
+            (0..26)
+                .filter(|q| answers.contains(*q))
+                .map(|q| (b'a' + q as u8) as char)
+                .collect()
+        };
+
+        assert_eq!(letters(group.any()), "abcde");
+        assert_eq!(letters(group.all()), "a");
+        assert_eq!(letters(group.exactly(1)), "cde");
+        assert_eq!(letters(group.exactly(2)), "b");
+        assert_eq!(letters(group.exactly(3)), "a");
+        assert_eq!(&group.histogram()[..6], &[3, 2, 1, 1, 1, 0]);
+    }
+
+    #[test]
+    fn invalid_answer() {
+        assert!(matches!(
+            parse("abc\n\nab\naC\n".as_bytes()),
+            Err(Error::Question {
+                line: 4,
+                column: 2,
+                character: 'C'
+            })
+        ));
+
+        // Not UTF-8, so the reader fails.
+        assert!(matches!(parse(&b"ab\n\xff\n"[..]), Err(Error::Io(_))));
+    }
 }
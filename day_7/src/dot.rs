@@ -0,0 +1,154 @@
+// Graphviz export of the bag graph, e.g.
+//
+//     cargo run --bin day_7 -- dot --descendants "shiny gold" --highlight "shiny gold" | dot -Tsvg
+//
+// Edges point from a bag to the bags it contains and are labelled with the count.
+
+use crate::graph::Graph;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+pub enum Limit<'b> {
+    // Only the bag and the bags that can eventually contain it.
+    Ancestors(&'b str),
+    // Only the bag and the bags it eventually contains.
+    Descendants(&'b str),
+}
+
+#[derive(Default)]
+pub struct DotOptions<'b> {
+    pub limit: Option<Limit<'b>>,
+    // Colours the bags part 1 visits (everything that can contain this bag) and the bags part 2
+    // visits (everything inside it).
+    pub highlight: Option<&'b str>,
+}
+
+const ROOT_COLOUR: &str = "gold";
+const PART_1_COLOUR: &str = "lightblue";
+const PART_2_COLOUR: &str = "palegreen";
+
+pub fn to_dot(graph: &Graph, options: &DotOptions) -> String {
+    let included: HashSet<&str> = match options.limit {
+        None => graph.bags().into_iter().collect(),
+        Some(Limit::Ancestors(id)) => with(graph.ancestors(id), id),
+        Some(Limit::Descendants(id)) => with(graph.descendants(id), id),
+    };
+
+    let (part_1, part_2) = match options.highlight {
+        Some(id) => (graph.ancestors(id), graph.descendants(id)),
+        None => (HashSet::new(), HashSet::new()),
+    };
+
+    let mut out = String::new();
+    writeln!(out, "digraph bags {{").unwrap();
+    writeln!(out, "    node [style=filled, fillcolor=white];").unwrap();
+
+    let bags: Vec<_> = graph
+        .bags()
+        .into_iter()
+        .filter(|bag| included.contains(bag))
+        .collect();
+
+    for bag in &bags {
+        let colour = if Some(*bag) == options.highlight {
+            Some(ROOT_COLOUR)
+        } else if part_1.contains(bag) {
+            Some(PART_1_COLOUR)
+        } else if part_2.contains(bag) {
+            Some(PART_2_COLOUR)
+        } else {
+            None
+        };
+
+        match colour {
+            Some(colour) => writeln!(out, "    {:?} [fillcolor={}];", bag, colour).unwrap(),
+            None => writeln!(out, "    {:?};", bag).unwrap(),
+        }
+    }
+
+    for bag in &bags {
+        for (inner, count) in graph.find_outgoing(bag) {
+            if included.contains(inner) {
+                writeln!(out, "    {:?} -> {:?} [label={}];", bag, inner, count).unwrap();
+            }
+        }
+    }
+
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+fn with<'a>(mut bags: HashSet<&'a str>, id: &'a str) -> HashSet<&'a str> {
+    bags.insert(id);
+    bags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> Graph<'static> {
+        let mut builder = Graph::builder();
+        builder.add_edge("light red", "shiny gold", 1);
+        builder.add_edge("shiny gold", "dark olive", 2);
+        builder.add_edge("dark olive", "faded blue", 3);
+        builder.add_edge("light red", "faded blue", 4);
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn all() {
+        assert_eq!(
+            to_dot(&graph(), &DotOptions::default()),
+            r#"digraph bags {
+    node [style=filled, fillcolor=white];
+    "dark olive";
+    "faded blue";
+    "light red";
+    "shiny gold";
+    "dark olive" -> "faded blue" [label=3];
+    "light red" -> "faded blue" [label=4];
+    "light red" -> "shiny gold" [label=1];
+    "shiny gold" -> "dark olive" [label=2];
+}
+"#
+        );
+    }
+
+    #[test]
+    fn limited_and_highlighted() {
+        let options = DotOptions {
+            limit: Some(Limit::Descendants("shiny gold")),
+            highlight: Some("shiny gold"),
+        };
+
+        assert_eq!(
+            to_dot(&graph(), &options),
+            r#"digraph bags {
+    node [style=filled, fillcolor=white];
+    "dark olive" [fillcolor=palegreen];
+    "faded blue" [fillcolor=palegreen];
+    "shiny gold" [fillcolor=gold];
+    "dark olive" -> "faded blue" [label=3];
+    "shiny gold" -> "dark olive" [label=2];
+}
+"#
+        );
+
+        let options = DotOptions {
+            limit: Some(Limit::Ancestors("shiny gold")),
+            highlight: Some("shiny gold"),
+        };
+
+        assert_eq!(
+            to_dot(&graph(), &options),
+            r#"digraph bags {
+    node [style=filled, fillcolor=white];
+    "light red" [fillcolor=lightblue];
+    "shiny gold" [fillcolor=gold];
+    "light red" -> "shiny gold" [label=1];
+}
+"#
+        );
+    }
+}
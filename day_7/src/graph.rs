@@ -0,0 +1,319 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+// Bags and the bags they must directly contain. Edges are indexed in both directions, and once the
+// graph is built it's known to be acyclic, so containment totals can be filled in bottom up.
+pub struct Graph<'a> {
+    outgoing: HashMap<&'a str, HashMap<&'a str, usize>>,
+    incoming: HashMap<&'a str, HashMap<&'a str, usize>>,
+    // Every bag, each one before any of the bags it contains.
+    order: Vec<&'a str>,
+    // How many bags each bag contains in total.
+    totals: HashMap<&'a str, usize>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Cycle(pub Vec<String>);
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "bags contain themselves: {}", self.0.join(" -> "))
+    }
+}
+
+#[derive(Default)]
+pub struct GraphBuilder<'a> {
+    edges: Vec<(&'a str, &'a str, usize)>,
+    nodes: Vec<&'a str>,
+}
+
+impl<'a> GraphBuilder<'a> {
+    pub fn add_node(&mut self, id: &'a str) {
+        self.nodes.push(id);
+    }
+
+    pub fn add_edge(&mut self, from: &'a str, to: &'a str, weight: usize) {
+        self.edges.push((from, to, weight));
+    }
+
+    pub fn build(self) -> Result<Graph<'a>, Cycle> {
+        let mut outgoing: HashMap<_, HashMap<_, _>> = HashMap::new();
+        let mut incoming: HashMap<_, HashMap<_, _>> = HashMap::new();
+
+        for id in self.nodes {
+            outgoing.entry(id).or_default();
+            incoming.entry(id).or_default();
+        }
+
+        for (from, to, weight) in self.edges {
+            outgoing.entry(from).or_default().insert(to, weight);
+            outgoing.entry(to).or_default();
+            incoming.entry(to).or_default().insert(from, weight);
+            incoming.entry(from).or_default();
+        }
+
+        let mut graph = Graph {
+            outgoing,
+            incoming,
+            order: Vec::new(),
+            totals: HashMap::new(),
+        };
+        graph.order = graph.sort()?;
+        graph.totals = graph.count_totals();
+
+        Ok(graph)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mark {
+    Visiting,
+    Done,
+}
+
+impl<'a> Graph<'a> {
+    pub fn builder() -> GraphBuilder<'a> {
+        GraphBuilder::default()
+    }
+
+    pub fn find_outgoing(&self, id: &str) -> Vec<(&'a str, usize)> {
+        Self::edges(&self.outgoing, id)
+    }
+
+    fn edges(index: &HashMap<&'a str, HashMap<&'a str, usize>>, id: &str) -> Vec<(&'a str, usize)> {
+        let mut edges: Vec<_> = index
+            .get(id)
+            .map(|hash| hash.iter().map(|(id, weight)| (*id, *weight)).collect())
+            .unwrap_or_default();
+        edges.sort_unstable();
+        edges
+    }
+
+    // Every bag, sorted by name.
+    pub fn bags(&self) -> Vec<&'a str> {
+        let mut bags = self.order.clone();
+        bags.sort_unstable();
+        bags
+    }
+
+    pub fn has_bag(&self, id: &str) -> bool {
+        self.outgoing.contains_key(id)
+    }
+
+    // Every bag that can eventually contain `id`.
+    pub fn ancestors(&self, id: &str) -> HashSet<&'a str> {
+        Self::reachable(&self.incoming, id)
+    }
+
+    // Every bag that `id` eventually contains.
+    pub fn descendants(&self, id: &str) -> HashSet<&'a str> {
+        Self::reachable(&self.outgoing, id)
+    }
+
+    fn reachable(index: &HashMap<&'a str, HashMap<&'a str, usize>>, id: &str) -> HashSet<&'a str> {
+        let mut seen = HashSet::new();
+        let mut stack = vec![id];
+
+        while let Some(node) = stack.pop() {
+            for (next, _) in Self::edges(index, node) {
+                if seen.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+
+        seen
+    }
+
+    // How many bags a single `id` bag contains in total.
+    pub fn total_contained(&self, id: &str) -> usize {
+        self.totals.get(id).copied().unwrap_or(0)
+    }
+
+    // The longest chain of bags nested inside `id`, starting with `id` itself.
+    pub fn deepest_chain(&self, id: &str) -> Vec<&'a str> {
+        let id = match self.outgoing.get_key_value(id) {
+            Some((id, _)) => *id,
+            None => return Vec::new(),
+        };
+
+        // Deepest chain length below each bag, and the bag to follow to get there. Walking the
+        // topological order backwards fills in every bag's contents first.
+        let mut deepest: HashMap<&str, (usize, Option<&str>)> = HashMap::new();
+
+        for node in self.order.iter().rev() {
+            let best = self
+                .find_outgoing(node)
+                .into_iter()
+                .map(|(child, _)| (deepest[child].0 + 1, Some(child)))
+                // Ties go to the first bag alphabetically.
+                .fold(
+                    (0, None),
+                    |best, next| if next.0 > best.0 { next } else { best },
+                );
+            deepest.insert(node, best);
+        }
+
+        let mut chain = vec![id];
+        let mut node = id;
+        while let Some((_, Some(next))) = deepest.get(node) {
+            chain.push(next);
+            node = next;
+        }
+
+        chain
+    }
+
+    // How many `inner` bags a single `outer` bag eventually contains, counting every path.
+    pub fn count_inside(&self, outer: &str, inner: &str) -> usize {
+        let mut copies: HashMap<&str, usize> = HashMap::new();
+        copies.insert(outer, 1);
+
+        // Containers come first, so every path into a bag has been counted before it's expanded.
+        for node in &self.order {
+            let count = match copies.get(node) {
+                Some(count) => *count,
+                None => continue,
+            };
+
+            for (child, weight) in self.find_outgoing(node) {
+                *copies.entry(child).or_insert(0) += count * weight;
+            }
+        }
+
+        if outer == inner {
+            return 0;
+        }
+        copies.get(inner).copied().unwrap_or(0)
+    }
+
+    // Depth first search, returning bags in reverse post-order, or the first cycle found.
+    fn sort(&self) -> Result<Vec<&'a str>, Cycle> {
+        let mut nodes: Vec<_> = self.outgoing.keys().copied().collect();
+        nodes.sort_unstable();
+
+        let mut marks = HashMap::new();
+        let mut post_order = Vec::with_capacity(nodes.len());
+
+        for node in nodes {
+            let mut path = Vec::new();
+            self.visit(node, &mut marks, &mut path, &mut post_order)?;
+        }
+
+        post_order.reverse();
+        Ok(post_order)
+    }
+
+    fn visit(
+        &self,
+        node: &'a str,
+        marks: &mut HashMap<&'a str, Mark>,
+        path: &mut Vec<&'a str>,
+        post_order: &mut Vec<&'a str>,
+    ) -> Result<(), Cycle> {
+        match marks.get(node) {
+            Some(Mark::Done) => return Ok(()),
+            Some(Mark::Visiting) => {
+                let start = path.iter().position(|n| *n == node).unwrap();
+                let mut cycle: Vec<_> = path[start..].iter().map(|n| n.to_string()).collect();
+                cycle.push(node.to_owned());
+                return Err(Cycle(cycle));
+            }
+            None => {}
+        }
+
+        marks.insert(node, Mark::Visiting);
+        path.push(node);
+
+        for (child, _) in self.find_outgoing(node) {
+            self.visit(child, marks, path, post_order)?;
+        }
+
+        path.pop();
+        marks.insert(node, Mark::Done);
+        post_order.push(node);
+
+        Ok(())
+    }
+
+    // Contents come after their containers in the topological order, so walking it backwards
+    // means every bag's contents have already been counted.
+    fn count_totals(&self) -> HashMap<&'a str, usize> {
+        let mut totals = HashMap::with_capacity(self.order.len());
+
+        for node in self.order.iter().rev() {
+            let total = self
+                .find_outgoing(node)
+                .iter()
+                .map(|(child, count)| count + count * totals[child])
+                .sum::<usize>();
+            totals.insert(*node, total);
+        }
+
+        totals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_cycles() {
+        let mut builder = Graph::builder();
+        builder.add_edge("shiny gold", "dark red", 2);
+        builder.add_edge("dark red", "dark orange", 2);
+        builder.add_edge("dark orange", "shiny gold", 1);
+        builder.add_edge("dark orange", "dark blue", 3);
+
+        assert_eq!(
+            builder.build().err(),
+            Some(Cycle(vec![
+                "dark orange".to_owned(),
+                "shiny gold".to_owned(),
+                "dark red".to_owned(),
+                "dark orange".to_owned(),
+            ]))
+        );
+
+        let mut builder = Graph::builder();
+        builder.add_edge("shiny gold", "shiny gold", 1);
+        assert_eq!(
+            builder.build().err().unwrap().to_string(),
+            "bags contain themselves: shiny gold -> shiny gold"
+        );
+    }
+
+    #[test]
+    fn topological_order() {
+        let mut builder = Graph::builder();
+        builder.add_edge("light red", "bright white", 1);
+        builder.add_edge("light red", "muted yellow", 2);
+        builder.add_edge("bright white", "shiny gold", 1);
+        builder.add_edge("muted yellow", "shiny gold", 2);
+        builder.add_node("faded blue");
+        let graph = builder.build().unwrap();
+
+        let order = &graph.order;
+        let position = |id| order.iter().position(|n| *n == id).unwrap();
+
+        assert_eq!(order.len(), 5);
+        assert!(position("light red") < position("bright white"));
+        assert!(position("light red") < position("muted yellow"));
+        assert!(position("bright white") < position("shiny gold"));
+        assert!(position("muted yellow") < position("shiny gold"));
+
+        assert_eq!(graph.total_contained("light red"), 1 + 1 + 2 + 2 * 2);
+        assert_eq!(graph.total_contained("faded blue"), 0);
+        assert_eq!(graph.ancestors("shiny gold").len(), 3);
+        assert_eq!(graph.descendants("light red").len(), 3);
+
+        assert_eq!(
+            graph.deepest_chain("light red"),
+            vec!["light red", "bright white", "shiny gold"]
+        );
+        assert_eq!(graph.deepest_chain("faded blue"), vec!["faded blue"]);
+        assert_eq!(graph.count_inside("light red", "shiny gold"), 1 + 2 * 2);
+        assert_eq!(graph.count_inside("shiny gold", "light red"), 0);
+    }
+}
@@ -82,6 +82,20 @@ fn main() {
         .map(|l| l.to_owned())
         .collect();
 
+    // cargo run --bin day_7 -- <command> [options]
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        let graph = graph(&input).unwrap_or_else(|cycle| panic!("{}", cycle));
+        match command(&graph, &args) {
+            Ok(output) => print!("{}", output),
+            Err(error) => {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
     let part_1 = part_1(&input);
     assert_eq!(part_1, 139);
     println!("Part 1: {}", part_1);
@@ -91,43 +105,53 @@ fn main() {
     println!("Part 2: {}", part_2);
 }
 
-use std::collections::{HashMap, HashSet};
-
-struct Graph<'a> {
-    graph: HashMap<&'a str, HashMap<&'a str, usize>>,
-}
-
-impl<'a> Graph<'a> {
-    fn new() -> Self {
-        Self {
-            graph: HashMap::new(),
+mod dot;
+mod graph;
+mod query;
+
+use dot::{DotOptions, Limit};
+use graph::{Cycle, Graph};
+use query::Query;
+
+const USAGE: &str = "usage:
+    day_7 contained-by <bag>
+    day_7 total <bag>
+    day_7 deepest <bag>
+    day_7 contains <bag> <bag>
+    day_7 dot [--ancestors <bag> | --descendants <bag>] [--highlight <bag>]";
+
+fn command(graph: &Graph, args: &[String]) -> Result<String, String> {
+    let query = match args {
+        [command, bag] if command == "contained-by" => Query::ContainedBy(bag),
+        [command, bag] if command == "total" => Query::Total(bag),
+        [command, bag] if command == "deepest" => Query::Deepest(bag),
+        [command, outer, inner] if command == "contains" => Query::Contains(outer, inner),
+        [command, ..] if command == "dot" => {
+            let mut options = DotOptions::default();
+            let mut iter = args[1..].iter();
+
+            while let Some(flag) = iter.next() {
+                let bag = iter.next().ok_or(USAGE)?;
+                match flag.as_str() {
+                    "--ancestors" => options.limit = Some(Limit::Ancestors(bag)),
+                    "--descendants" => options.limit = Some(Limit::Descendants(bag)),
+                    "--highlight" => options.highlight = Some(bag),
+                    _ => return Err(USAGE.to_owned()),
+                }
+            }
+
+            return Ok(dot::to_dot(graph, &options));
         }
-    }
-
-    fn add_edge(&mut self, from: &'a str, to: &'a str, weight: usize) {
-        self.graph
-            .entry(from)
-            .or_insert_with(HashMap::new)
-            .insert(to, weight);
-    }
+        _ => return Err(USAGE.to_owned()),
+    };
 
-    fn find_incoming(&self, id: &'a str) -> Vec<&'a str> {
-        self.graph
-            .iter()
-            .filter_map(|(outter_id, inner_map)| inner_map.get(id).map(|_| *outter_id))
-            .collect()
-    }
-
-    fn find_outgoing(&self, id: &str) -> Vec<(&'a str, usize)> {
-        self.graph
-            .get(id)
-            .map(|hash| hash.iter().map(|(id, weight)| (*id, *weight)).collect())
-            .unwrap_or_default()
-    }
+    query::query(graph, &query)
+        .map(|answer| answer.to_string())
+        .map_err(|error| error.to_string())
 }
 
-fn graph(input: &[String]) -> Graph {
-    let mut graph = Graph::new();
+fn graph(input: &[String]) -> Result<Graph<'_>, Cycle> {
+    let mut builder = Graph::builder();
 
     for line in input {
         let mut iter = line.split(" bags contain ");
@@ -155,45 +179,31 @@ fn graph(input: &[String]) -> Graph {
                 .collect()
         };
 
+        builder.add_node(name);
         for (rule, count) in rules {
-            graph.add_edge(name, rule, count);
+            builder.add_edge(name, rule, count);
         }
     }
 
-    graph
+    builder.build()
 }
 
 fn part_1(input: &[String]) -> usize {
-    let graph = graph(input);
-
-    fn neighbors<'a>(graph: &'a Graph, node: &'a str) -> HashSet<&'a str> {
-        graph
-            .find_incoming(node)
-            .iter()
-            .map(|n| {
-                let mut x = neighbors(graph, n);
-                x.insert(n);
-                x
-            })
-            .flatten()
-            .collect()
-    }
+    let graph = graph(input).unwrap_or_else(|cycle| panic!("{}", cycle));
 
-    neighbors(&graph, &"shiny gold").len()
+    match query::query(&graph, &Query::ContainedBy("shiny gold")) {
+        Ok(query::Answer::Bags(bags)) => bags.len(),
+        _ => panic!("no shiny gold bags"),
+    }
 }
 
 fn part_2(input: &[String]) -> usize {
-    let graph = graph(input);
-
-    fn sum_bag_counts<'a>(graph: &'a Graph, node: &'a str) -> usize {
-        graph
-            .find_outgoing(node)
-            .iter()
-            .map(|(n, count)| count + (count * sum_bag_counts(graph, n)))
-            .sum::<usize>()
-    }
+    let graph = graph(input).unwrap_or_else(|cycle| panic!("{}", cycle));
 
-    sum_bag_counts(&graph, &"shiny gold")
+    match query::query(&graph, &Query::Total("shiny gold")) {
+        Ok(query::Answer::Count(count)) => count,
+        _ => panic!("no shiny gold bags"),
+    }
 }
 
 #[cfg(test)]
@@ -202,7 +212,7 @@ mod tests {
 
     #[test]
     fn readme_example() {
-        let input: Vec<_> = vec![
+        let input: Vec<_> = [
             "light red bags contain 1 bright white bag, 2 muted yellow bags.",
             "dark orange bags contain 3 bright white bags, 4 muted yellow bags.",
             "bright white bags contain 1 shiny gold bag.",
@@ -220,4 +230,23 @@ mod tests {
         assert_eq!(part_1(&input), 4);
         assert_eq!(part_2(&input), 32);
     }
+
+    #[test]
+    fn readme_example_part_2() {
+        let input: Vec<_> = [
+            "shiny gold bags contain 2 dark red bags.",
+            "dark red bags contain 2 dark orange bags.",
+            "dark orange bags contain 2 dark yellow bags.",
+            "dark yellow bags contain 2 dark green bags.",
+            "dark green bags contain 2 dark blue bags.",
+            "dark blue bags contain 2 dark violet bags.",
+            "dark violet bags contain no other bags.",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        // In this example, a single shiny gold bag must contain 126 other bags.
+        assert_eq!(part_2(&input), 126);
+    }
 }
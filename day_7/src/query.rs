@@ -0,0 +1,137 @@
+// Questions that can be asked about any bag, not just shiny gold.
+//
+//     cargo run --bin day_7 -- contained-by "shiny gold"
+//     cargo run --bin day_7 -- total "shiny gold"
+//     cargo run --bin day_7 -- deepest "shiny gold"
+//     cargo run --bin day_7 -- contains "shiny gold" "dotted black"
+
+use crate::graph::Graph;
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub enum Query<'b> {
+    // Which bags can eventually contain this bag?
+    ContainedBy(&'b str),
+    // How many bags does this bag contain in total?
+    Total(&'b str),
+    // What is the deepest chain of bags nested inside this bag?
+    Deepest(&'b str),
+    // How many of the second bag does the first bag eventually contain?
+    Contains(&'b str, &'b str),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Answer<'a> {
+    Bags(Vec<&'a str>),
+    Count(usize),
+    Chain(Vec<&'a str>),
+}
+
+impl fmt::Display for Answer<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Bags(bags) => bags.iter().try_for_each(|bag| writeln!(f, "{}", bag)),
+            Answer::Count(count) => writeln!(f, "{}", count),
+            Answer::Chain(chain) => writeln!(f, "{}", chain.join(" -> ")),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct UnknownBag(pub String);
+
+impl fmt::Display for UnknownBag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "no rules mention {:?} bags", self.0)
+    }
+}
+
+pub fn query<'a>(graph: &Graph<'a>, query: &Query) -> Result<Answer<'a>, UnknownBag> {
+    let known = |id: &str| {
+        if graph.has_bag(id) {
+            Ok(())
+        } else {
+            Err(UnknownBag(id.to_owned()))
+        }
+    };
+
+    let answer = match *query {
+        Query::ContainedBy(id) => {
+            known(id)?;
+            let mut bags: Vec<_> = graph.ancestors(id).into_iter().collect();
+            bags.sort_unstable();
+            Answer::Bags(bags)
+        }
+        Query::Total(id) => {
+            known(id)?;
+            Answer::Count(graph.total_contained(id))
+        }
+        Query::Deepest(id) => {
+            known(id)?;
+            Answer::Chain(graph.deepest_chain(id))
+        }
+        Query::Contains(outer, inner) => {
+            known(outer)?;
+            known(inner)?;
+            Answer::Count(graph.count_inside(outer, inner))
+        }
+    };
+
+    Ok(answer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> Graph<'static> {
+        let mut builder = Graph::builder();
+        builder.add_edge("light red", "bright white", 1);
+        builder.add_edge("light red", "muted yellow", 2);
+        builder.add_edge("bright white", "shiny gold", 1);
+        builder.add_edge("muted yellow", "shiny gold", 2);
+        builder.add_edge("muted yellow", "faded blue", 9);
+        builder.add_edge("shiny gold", "faded blue", 3);
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn queries() {
+        let graph = graph();
+
+        assert_eq!(
+            query(&graph, &Query::ContainedBy("shiny gold")),
+            Ok(Answer::Bags(vec![
+                "bright white",
+                "light red",
+                "muted yellow"
+            ]))
+        );
+        assert_eq!(
+            query(&graph, &Query::Total("shiny gold")),
+            Ok(Answer::Count(3))
+        );
+        assert_eq!(
+            query(&graph, &Query::Deepest("light red")),
+            Ok(Answer::Chain(vec![
+                "light red",
+                "bright white",
+                "shiny gold",
+                "faded blue"
+            ]))
+        );
+        // 2 muted yellow with 9 each, plus 5 shiny gold with 3 each.
+        assert_eq!(
+            query(&graph, &Query::Contains("light red", "faded blue")),
+            Ok(Answer::Count(2 * 9 + 5 * 3))
+        );
+        assert_eq!(
+            query(&graph, &Query::Contains("faded blue", "light red")),
+            Ok(Answer::Count(0))
+        );
+        assert_eq!(
+            query(&graph, &Query::Total("plaid")),
+            Err(UnknownBag("plaid".to_owned()))
+        );
+    }
+}
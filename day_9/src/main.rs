@@ -102,79 +102,163 @@
 //
 // What is the encryption weakness in your XMAS-encrypted list of numbers?
 
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::ops::Range;
+
 fn main() {
     let input = include_str!("../input");
 
-    let part_1 = part_1(&input, 25);
+    let part_1 = part_1(input, 25).unwrap();
     assert_eq!(part_1, 177777905);
     println!("Part 1: {}", part_1);
 
-    let part_2 = part_2(&input, 25);
+    let part_2 = part_2(input, 25).unwrap();
     assert_eq!(part_2, 23463012);
     println!("Part 2: {}", part_2);
 }
 
+#[derive(Debug, PartialEq)]
+enum Error {
+    NoInvalidNumber,
+    NoRange(usize),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::NoInvalidNumber => write!(f, "every number is valid"),
+            Error::NoRange(n) => write!(f, "no contiguous range sums to {}", n),
+        }
+    }
+}
+
 fn parse(input: &str) -> Vec<usize> {
     input.lines().map(|n| n.parse::<usize>().unwrap()).collect()
 }
 
-fn part_1(input: &str, preamble_len: usize) -> usize {
+fn part_1(input: &str, preamble_len: usize) -> Result<usize, Error> {
     let input = parse(input);
-    invalid_number(&input, preamble_len)
+
+    invalid_numbers(&input, preamble_len)
+        .first()
+        .map(|(_, n)| *n)
+        .ok_or(Error::NoInvalidNumber)
+}
+
+// The previous `len` numbers, along with how many ways each sum can be made from two different
+// numbers in the window. Sliding the window updates the sums involving the numbers that leave
+// and enter, so checking a number is a single lookup.
+struct Window {
+    numbers: VecDeque<usize>,
+    sums: HashMap<usize, usize>,
 }
 
-fn invalid_number(input: &[usize], preamble_len: usize) -> usize {
-    *input
-        .iter()
-        .skip(preamble_len + 1)
-        .enumerate()
-        .find(|(i, n)| {
-            for x in input.iter().skip(*i) {
-                for y in input.iter().skip(*i) {
-                    if x + y == **n {
-                        // If we find a matching sum, we continue searching for an invalid number
-                        return false;
-                    }
-                }
+impl Window {
+    fn new(preamble: &[usize]) -> Self {
+        let mut window = Window {
+            numbers: VecDeque::with_capacity(preamble.len()),
+            sums: HashMap::new(),
+        };
+
+        for n in preamble {
+            window.push(*n);
+        }
+
+        window
+    }
+
+    fn push(&mut self, n: usize) {
+        for x in self.numbers.iter().filter(|x| **x != n) {
+            *self.sums.entry(x + n).or_insert(0) += 1;
+        }
+
+        self.numbers.push_back(n);
+    }
+
+    fn pop(&mut self) {
+        let n = self.numbers.pop_front().unwrap();
+
+        for x in self.numbers.iter().filter(|x| **x != n) {
+            let count = self.sums.get_mut(&(x + n)).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                self.sums.remove(&(x + n));
             }
+        }
+    }
 
-            true
-        })
-        .unwrap()
-        .1
+    fn is_sum(&self, n: usize) -> bool {
+        self.sums.contains_key(&n)
+    }
 }
 
-fn part_2(input: &str, preamble_len: usize) -> usize {
-    let input = parse(input);
-    let invalid_number = invalid_number(&input, preamble_len);
-
-    for i in 0..input.len() {
-        let mut acc = 0;
-
-        let mut j = i;
-        loop {
-            acc += input[j];
-
-            match acc {
-                x if x == invalid_number => {
-                    // When we find the invalid number, find the min and max values from the range and
-                    // add return the sum.
-                    let mut range = input[i..j].to_vec();
-                    range.sort_unstable();
-                    return range.first().unwrap() + range.last().unwrap();
-                }
-                x if x > invalid_number => {
-                    // If we go past the invalid number, break and try again on the next iteration
-                    break;
-                }
-                _ => {}
-            }
+// Every number after the preamble that isn't the sum of two different numbers among the
+// `preamble_len` numbers before it, with its index.
+fn invalid_numbers(input: &[usize], preamble_len: usize) -> Vec<(usize, usize)> {
+    if input.len() <= preamble_len {
+        return Vec::new();
+    }
 
-            j += 1;
+    let mut window = Window::new(&input[..preamble_len]);
+    let mut invalid = Vec::new();
+
+    for (i, n) in input.iter().enumerate().skip(preamble_len) {
+        if !window.is_sum(*n) {
+            invalid.push((i, *n));
+        }
+
+        // An empty window stays empty, so with no preamble every number is invalid.
+        if preamble_len > 0 {
+            window.pop();
+            window.push(*n);
+        }
+    }
+
+    invalid
+}
+
+// A contiguous range of at least two numbers that sum to `target`. All the numbers are positive,
+// so the range only ever grows at the end or shrinks at the start.
+fn contiguous_range(input: &[usize], target: usize) -> Option<Range<usize>> {
+    let mut start = 0;
+    let mut sum = 0;
+
+    for (end, n) in input.iter().enumerate() {
+        sum += n;
+
+        while sum > target {
+            sum -= input[start];
+            start += 1;
+        }
+
+        if sum == target && end > start {
+            return Some(start..end + 1);
         }
     }
 
-    panic!("No matching range of numbers");
+    None
+}
+
+fn part_2(input: &str, preamble_len: usize) -> Result<usize, Error> {
+    let input = parse(input);
+    let (index, invalid_number) = *invalid_numbers(&input, preamble_len)
+        .first()
+        .ok_or(Error::NoInvalidNumber)?;
+
+    // The invalid number itself is a range of one, so it can't be part of the answer, but the
+    // range can be on either side of it.
+    let after = index + 1;
+    let range = contiguous_range(&input[..index], invalid_number)
+        .or_else(|| {
+            contiguous_range(&input[after..], invalid_number)
+                .map(|range| range.start + after..range.end + after)
+        })
+        .ok_or(Error::NoRange(invalid_number))?;
+
+    // Add together the smallest and largest number in the range.
+    let range = &input[range];
+    Ok(range.iter().min().unwrap() + range.iter().max().unwrap())
 }
 
 #[cfg(test)]
@@ -204,7 +288,35 @@ mod tests {
 309
 576";
 
-        assert_eq!(part_1(&input, 5), 127);
-        assert_eq!(part_2(&input, 5), 62);
+        assert_eq!(part_1(input, 5), Ok(127));
+        assert_eq!(part_2(input, 5), Ok(62));
+    }
+
+    #[test]
+    fn invalid_numbers_in_window() {
+        // 8 would be 4 + 4, but the two numbers must be different. 12 would be 1 + 11, but 1 has
+        // left the window by then.
+        let input = [1, 2, 3, 4, 8, 7, 11, 12, 18];
+
+        assert_eq!(invalid_numbers(&input, 3), vec![(4, 8), (7, 12)]);
+        assert_eq!(invalid_numbers(&input, 9), vec![]);
+        assert_eq!(invalid_numbers(&input[..2], 0), vec![(0, 1), (1, 2)]);
+        assert_eq!(part_1("1\n2\n3", 0), Ok(1));
+    }
+
+    #[test]
+    fn ranges() {
+        let input = [1, 2, 3, 4, 5];
+
+        assert_eq!(contiguous_range(&input, 9), Some(1..4));
+        assert_eq!(contiguous_range(&input, 5), Some(1..3));
+        // A range of one number doesn't count.
+        assert_eq!(contiguous_range(&[5, 1], 5), None);
+        assert_eq!(contiguous_range(&input, 100), None);
+
+        assert_eq!(part_2("1\n2\n3\n50", 3), Err(Error::NoRange(50)));
+        // The range comes after the invalid number.
+        assert_eq!(part_2("1\n2\n3\n100\n50\n50", 3), Ok(100));
+        assert_eq!(part_1("1\n2\n3\n5", 2), Err(Error::NoInvalidNumber));
     }
 }
@@ -0,0 +1,132 @@
+// Reads blank-line-separated batches (day 4 passports, day 6 answer groups) from any `BufRead`
+// one record at a time, instead of loading the whole file and splitting on "\n\n".
+//
+// Both "\n" and "\r\n" line endings are accepted, runs of blank lines (including any at the end of
+// the input) never produce empty records, and every record knows the line it started on.
+
+use std::io::{self, BufRead};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    // 1-based line number of the first line in the record.
+    pub line: usize,
+    pub lines: Vec<String>,
+}
+
+impl Record {
+    // Each line paired with its 1-based line number.
+    pub fn numbered_lines(&self) -> impl Iterator<Item = (usize, &str)> {
+        self.lines
+            .iter()
+            .enumerate()
+            .map(move |(i, line)| (self.line + i, line.as_str()))
+    }
+}
+
+pub struct Records<R> {
+    reader: R,
+    line: usize,
+    buf: String,
+}
+
+pub fn records<R: BufRead>(reader: R) -> Records<R> {
+    Records {
+        reader,
+        line: 0,
+        buf: String::new(),
+    }
+}
+
+impl<R: BufRead> Iterator for Records<R> {
+    type Item = io::Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut record: Option<Record> = None;
+
+        loop {
+            self.buf.clear();
+            match self.reader.read_line(&mut self.buf) {
+                Ok(0) => return record.map(Ok),
+                Ok(_) => {}
+                Err(e) => return Some(Err(e)),
+            }
+            self.line += 1;
+
+            let line = self.buf.trim_end_matches(&['\n', '\r'][..]);
+
+            if line.trim().is_empty() {
+                if record.is_some() {
+                    return record.map(Ok);
+                }
+                continue;
+            }
+
+            record
+                .get_or_insert_with(|| Record {
+                    line: self.line,
+                    lines: Vec::new(),
+                })
+                .lines
+                .push(line.to_owned());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(input: &str) -> Vec<Record> {
+        records(input.as_bytes()).collect::<io::Result<_>>().unwrap()
+    }
+
+    #[test]
+    fn splits_on_blank_lines() {
+        let records = read("abc\n\na\nb\nc\n\nab\nac\n");
+
+        assert_eq!(
+            records,
+            vec![
+                Record {
+                    line: 1,
+                    lines: vec!["abc".to_owned()]
+                },
+                Record {
+                    line: 3,
+                    lines: vec!["a".to_owned(), "b".to_owned(), "c".to_owned()]
+                },
+                Record {
+                    line: 7,
+                    lines: vec!["ab".to_owned(), "ac".to_owned()]
+                },
+            ]
+        );
+        assert_eq!(
+            records[1].numbered_lines().collect::<Vec<_>>(),
+            vec![(3, "a"), (4, "b"), (5, "c")]
+        );
+    }
+
+    #[test]
+    fn crlf_and_extra_blank_lines() {
+        let records = read("\r\n\r\nab\r\nac\r\n\r\n\r\n  \r\nb\r\n\r\n\r\n");
+
+        assert_eq!(
+            records,
+            vec![
+                Record {
+                    line: 3,
+                    lines: vec!["ab".to_owned(), "ac".to_owned()]
+                },
+                Record {
+                    line: 8,
+                    lines: vec!["b".to_owned()]
+                },
+            ]
+        );
+
+        assert_eq!(read("a"), read("a\n"));
+        assert!(read("").is_empty());
+        assert!(read("\n\n\n").is_empty());
+    }
+}